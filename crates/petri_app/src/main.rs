@@ -1,10 +1,24 @@
 use bevy::{diagnostic::LogDiagnosticsPlugin, prelude::*};
 use petri_simulation as sim;
-use sim::SimulationPlugin;
+use sim::{SimulationPlugin, SimulationSeed};
+use std::env;
 
 fn main() {
-    App::new()
-        .insert_resource(ClearColor(Color::MIDNIGHT_BLUE))
+    let mut app = App::new();
+
+    // Pass the seed logged by a previous run as the first argument to replay it
+    if let Some(seed) = env::args().nth(1) {
+        let seed = seed.trim().parse().unwrap_or_else(|_| {
+            panic!(
+                "The seed argument must be an unsigned 64-bit integer, found {:?}",
+                seed
+            )
+        });
+
+        app.insert_resource(SimulationSeed(seed));
+    }
+
+    app.insert_resource(ClearColor(Color::MIDNIGHT_BLUE))
         .insert_resource(WindowDescriptor {
             title: "Petri World!".to_string(),
             width: 800.0,
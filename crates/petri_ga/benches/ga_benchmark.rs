@@ -17,6 +17,21 @@ impl TestIndividual {
     }
 }
 
+impl Candidate for TestIndividual {
+    fn fitness(&self) -> f32 {
+        match self {
+            Self::WithChromosome { chromosome } => {
+                chromosome.iter().sum()
+
+                // ^ the simplest fitness function ever - we're just
+                // summing all the genes together
+            }
+
+            Self::WithFitness { fitness } => *fitness,
+        }
+    }
+}
+
 impl Individual for TestIndividual {
     fn create(chromosome: Chromosome) -> Self {
         Self::WithChromosome { chromosome }
@@ -31,19 +46,6 @@ impl Individual for TestIndividual {
             }
         }
     }
-
-    fn fitness(&self) -> f32 {
-        match self {
-            Self::WithChromosome { chromosome } => {
-                chromosome.iter().sum()
-
-                // ^ the simplest fitness function ever - we're just
-                // summing all the genes together
-            }
-
-            Self::WithFitness { fitness } => *fitness,
-        }
-    }
 }
 
 fn individual(genes: &[f32]) -> TestIndividual {
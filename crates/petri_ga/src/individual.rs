@@ -1,7 +1,18 @@
 use crate::chromosome::Chromosome;
 
-pub trait Individual {
+/// Anything a `SelectionMethod` can pick by fitness, shared by `Individual` and
+/// `NeatIndividual`.
+pub trait Candidate {
     fn fitness(&self) -> f32;
+}
+
+impl<C: Candidate + ?Sized> Candidate for &C {
+    fn fitness(&self) -> f32 {
+        (**self).fitness()
+    }
+}
+
+pub trait Individual: Candidate {
     fn chromosome(&self) -> &Chromosome;
     fn create(chromosome: Chromosome) -> Self;
 }
@@ -23,6 +34,22 @@ impl TestIndividual {
     }
 }
 
+#[cfg(test)]
+impl Candidate for TestIndividual {
+    fn fitness(&self) -> f32 {
+        match self {
+            Self::WithChromosome { chromosome } => {
+                chromosome.iter().sum()
+
+                // ^ the simplest fitness function ever - we're just
+                // summing all the genes together
+            }
+
+            Self::WithFitness { fitness } => *fitness,
+        }
+    }
+}
+
 #[cfg(test)]
 impl Individual for TestIndividual {
     fn create(chromosome: Chromosome) -> Self {
@@ -38,17 +65,4 @@ impl Individual for TestIndividual {
             }
         }
     }
-
-    fn fitness(&self) -> f32 {
-        match self {
-            Self::WithChromosome { chromosome } => {
-                chromosome.iter().sum()
-
-                // ^ the simplest fitness function ever - we're just
-                // summing all the genes together
-            }
-
-            Self::WithFitness { fitness } => *fitness,
-        }
-    }
 }
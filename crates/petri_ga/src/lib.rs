@@ -4,6 +4,7 @@ mod chromosome;
 mod crossover;
 mod individual;
 mod mutation;
+mod neat;
 mod selection;
 mod statistics;
 
@@ -11,7 +12,7 @@ use petri_rand::PetriRand;
 use std::{iter::repeat_with, marker::PhantomData};
 
 pub use crate::{
-    chromosome::*, crossover::*, individual::*, mutation::*, selection::*, statistics::*,
+    chromosome::*, crossover::*, individual::*, mutation::*, neat::*, selection::*, statistics::*,
 };
 
 #[derive(Debug)]
@@ -113,7 +114,7 @@ mod genetic_algorithm {
 
         let child = mutator.mutate(&rng, crosser.crossover(&rng, &parent_a, &parent_b));
 
-        let expected_child = individual(&[1.4977746, 2.0, -3.489682, -4.0, 4.533561])
+        let expected_child = individual(&[1.6262689, 2.0, -2.9326093, -4.0, 5.1258116])
             .chromosome()
             .clone();
 
@@ -192,10 +193,10 @@ mod genetic_algorithm {
         }
 
         let expected_population = vec![
-            individual(&[1.9487331, 2.490075, 0.74217165]),
-            individual(&[2.141826, 2.6185188, 0.74217165]),
-            individual(&[2.0817406, 2.07515, 0.6272201]),
-            individual(&[1.9487331, 2.4159615, 1.4774871]),
+            individual(&[1.3219094, 0.8054346, 4.095987]),
+            individual(&[1.3219094, 0.49314174, 5.4700627]),
+            individual(&[1.3219094, 0.95842296, 4.857359]),
+            individual(&[1.5171756, 0.69875157, 4.107126]),
         ];
 
         assert_eq!(population, expected_population);
@@ -13,9 +13,10 @@ pub struct GaussianMutation {
     /// - 1.0 = all genes will be touched
     chance: f32,
 
-    /// Magnitude of that change:
+    /// Magnitude of that change, as the standard deviation of the normal
+    /// distribution it's drawn from:
     /// - 0.0 = touched genes will not be modified
-    /// - 3.0 = touched genes will be += or -= by at most 3.0
+    /// - 3.0 = touched genes will be += or -= by less than 3.0 about 68% of the time
     coeff: f32,
 }
 
@@ -32,8 +33,7 @@ impl MutationMethod for GaussianMutation {
         child
             .map(|gene| {
                 if rng.chance(self.chance as _) {
-                    let sign = if rng.bool() { -1.0 } else { 1.0 };
-                    gene + (sign * self.coeff * rng.get_f32())
+                    gene + rng.normal_f32(0.0, self.coeff)
                 } else {
                     gene
                 }
@@ -111,7 +111,7 @@ mod tests {
             fn slightly_changes_the_original_chromosome() {
                 let actual = actual(0.5);
 
-                let expected = vec![1.0, 2.4977746, 2.7933073, 3.510318, 4.849235];
+                let expected = vec![1.0, 2.6262689, 3.0274546, 4.067391, 3.9527988];
 
                 approx::assert_relative_eq!(actual.as_slice(), expected.as_slice());
             }
@@ -142,7 +142,7 @@ mod tests {
             fn entirely_changes_the_original_chromosome() {
                 let actual = actual(0.5);
 
-                let expected = vec![0.9441324, 2.4977746, 2.5183606, 4.0430183, 4.5103183];
+                let expected = vec![0.33311278, 2.6262689, 3.0415704, 4.818437, 5.067391];
 
                 approx::assert_relative_eq!(actual.as_slice(), expected.as_slice());
             }
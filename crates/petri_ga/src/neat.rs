@@ -0,0 +1,324 @@
+//! NEAT (NeuroEvolution of Augmenting Topologies): genomes that evolve their
+//! network's structure along with its weights, starting minimal and growing through
+//! structural mutations. Species protect new structures, and crossover lines genes
+//! up by innovation number.
+
+mod genome;
+mod speciation;
+
+use petri_rand::PetriRand;
+
+use crate::{Candidate, MutationMethod, SelectionMethod, Statistics, StatisticsBuilder};
+
+pub use self::{
+    genome::{ConnectionGene, Genome, InnovationHistory, NodeGene, NodeKind},
+    speciation::{Speciation, Species},
+};
+
+/// Species with at least this many members keep their fittest genome unchanged.
+const CHAMPION_SPECIES_SIZE: usize = 5;
+
+/// The NEAT counterpart of `Individual`, for individuals made from a `Genome`
+/// rather than a fixed length `Chromosome`.
+pub trait NeatIndividual: Candidate {
+    fn genome(&self) -> &Genome;
+    fn create(genome: Genome) -> Self;
+}
+
+#[derive(Clone, Debug)]
+pub struct StructuralMutation {
+    /// Probability of splitting a connection with a new node.
+    add_node: f32,
+    /// Probability of connecting two unconnected nodes.
+    add_connection: f32,
+}
+
+impl StructuralMutation {
+    pub fn new(add_node: f32, add_connection: f32) -> Self {
+        debug_assert!((0.0..=1.0).contains(&add_node));
+        debug_assert!((0.0..=1.0).contains(&add_connection));
+
+        Self {
+            add_node,
+            add_connection,
+        }
+    }
+
+    pub fn mutate(&self, rng: &PetriRand, genome: &mut Genome, history: &mut InnovationHistory) {
+        if rng.chance(self.add_node as _) {
+            genome.mutate_add_node(rng, history);
+        }
+
+        if rng.chance(self.add_connection as _) {
+            genome.mutate_add_connection(rng, history);
+        }
+    }
+}
+
+/// Evolves `NeatIndividual`s, much like `GeneticAlgorithm` does `Individual`s:
+/// parents are selected within their species, crossed over, then have their
+/// weights mutated by `mutation_method` and their structure by `structural_mutation`.
+///
+/// Species carry over between calls to `evolve`, so use one `Neat` per population.
+#[derive(Debug)]
+pub struct Neat<S: SelectionMethod, M: MutationMethod> {
+    selection_method: S,
+    mutation_method: M,
+    structural_mutation: StructuralMutation,
+    speciation: Speciation,
+    species: Vec<Species>,
+}
+
+impl<S, M> Neat<S, M>
+where
+    S: SelectionMethod,
+    M: MutationMethod,
+{
+    pub fn new(
+        selection_method: S,
+        mutation_method: M,
+        structural_mutation: StructuralMutation,
+        speciation: Speciation,
+    ) -> Self {
+        Self {
+            selection_method,
+            mutation_method,
+            structural_mutation,
+            speciation,
+            species: Vec::new(),
+        }
+    }
+
+    /// The species of the population last given to `evolve`.
+    pub fn species(&self) -> &[Species] {
+        &self.species
+    }
+
+    pub fn evolve<I>(
+        &mut self,
+        rng: &PetriRand,
+        population: &[I],
+        history: &mut InnovationHistory,
+    ) -> Option<(Vec<I>, Statistics)>
+    where
+        I: NeatIndividual,
+    {
+        if population.is_empty() {
+            return None;
+        }
+
+        let stats = StatisticsBuilder::from_population(population);
+
+        if stats.has_no_fitness() {
+            return None;
+        }
+
+        self.speciation.speciate(rng, &mut self.species, population);
+
+        // Explicit fitness sharing: a species earns offspring by the average fitness
+        // of its members, so a large one can't take over the whole population.
+        let shares: Vec<f32> = self
+            .species
+            .iter()
+            .map(|species| {
+                let members = species.members();
+
+                members
+                    .iter()
+                    .map(|&i| population[i].fitness())
+                    .sum::<f32>()
+                    / members.len() as f32
+            })
+            .collect();
+
+        let mut new_population = Vec::with_capacity(population.len());
+
+        for (species, offspring) in self.species.iter().zip(allot(&shares, population.len())) {
+            let members: Vec<&I> = species.members().iter().map(|&i| &population[i]).collect();
+            let mut offspring = offspring;
+
+            if offspring > 0 && members.len() >= CHAMPION_SPECIES_SIZE {
+                let champion = members
+                    .iter()
+                    .max_by(|a, b| a.fitness().total_cmp(&b.fitness()))
+                    .unwrap();
+
+                new_population.push(I::create(champion.genome().clone()));
+                offspring -= 1;
+            }
+
+            let total: f32 = members.iter().map(|individual| individual.fitness()).sum();
+            let selection_chance = |individual: &&I| -> f32 {
+                if total > 0.0 {
+                    individual.fitness() / total
+                } else {
+                    1.0 / members.len() as f32
+                }
+            };
+
+            for _ in 0..offspring {
+                let parent_a = self
+                    .selection_method
+                    .select(rng, &members, selection_chance)
+                    .unwrap();
+                let parent_b = self
+                    .selection_method
+                    .select(rng, &members, selection_chance)
+                    .unwrap();
+
+                let (fitter, other) = if parent_a.fitness() >= parent_b.fitness() {
+                    (parent_a, parent_b)
+                } else {
+                    (parent_b, parent_a)
+                };
+
+                let mut child = Genome::crossover(rng, fitter.genome(), other.genome());
+
+                child.mutate_weights(rng, &self.mutation_method);
+                self.structural_mutation.mutate(rng, &mut child, history);
+
+                new_population.push(I::create(child));
+            }
+        }
+
+        Some((new_population, stats))
+    }
+}
+
+/// Splits `total` offspring between species in proportion to their shares, giving
+/// what rounding down leaves over to the largest remainders.
+fn allot(shares: &[f32], total: usize) -> Vec<usize> {
+    let sum: f32 = shares.iter().sum();
+    let quotas: Vec<f32> = shares
+        .iter()
+        .map(|share| share / sum * total as f32)
+        .collect();
+
+    let mut offspring: Vec<usize> = quotas.iter().map(|quota| quota.floor() as usize).collect();
+
+    let mut by_remainder: Vec<usize> = (0..quotas.len()).collect();
+    by_remainder.sort_by(|&a, &b| quotas[b].fract().total_cmp(&quotas[a].fract()));
+
+    let missing = total.saturating_sub(offspring.iter().sum());
+
+    for &index in by_remainder.iter().cycle().take(missing) {
+        offspring[index] += 1;
+    }
+
+    offspring
+}
+
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestGenome {
+    genome: Genome,
+    fitness: f32,
+}
+
+#[cfg(test)]
+impl TestGenome {
+    pub fn new(genome: Genome, fitness: f32) -> Self {
+        Self { genome, fitness }
+    }
+}
+
+#[cfg(test)]
+impl Candidate for TestGenome {
+    fn fitness(&self) -> f32 {
+        self.fitness
+    }
+}
+
+#[cfg(test)]
+impl NeatIndividual for TestGenome {
+    fn genome(&self) -> &Genome {
+        &self.genome
+    }
+
+    fn create(genome: Genome) -> Self {
+        // Rewards growth, so that evolution has something to show for it
+        let fitness = genome.enabled_connections().count() as f32;
+
+        Self { genome, fitness }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GaussianMutation, RouletteWheelSelection};
+
+    #[test]
+    fn allot_offspring() {
+        assert_eq!(allot(&[1.0, 1.0], 4), vec![2, 2]);
+        assert_eq!(allot(&[1.0, 2.0], 4), vec![1, 3]);
+        assert_eq!(allot(&[1.0, 1.0, 1.0], 10), vec![4, 3, 3]);
+        assert_eq!(allot(&[0.0, 3.0], 5), vec![0, 5]);
+    }
+
+    #[test]
+    fn evolve() {
+        let rng = PetriRand::with_seed(Default::default());
+        let mut history = InnovationHistory::new(3, 2);
+
+        let mut neat = Neat::new(
+            RouletteWheelSelection::new(),
+            GaussianMutation::new(0.5, 0.5),
+            StructuralMutation::new(0.3, 0.3),
+            Speciation::new(3.0),
+        );
+
+        let mut population: Vec<TestGenome> = (0..20)
+            .map(|_| TestGenome::create(Genome::minimal(&rng, &mut history, 3, 2)))
+            .collect();
+
+        let (_, initial) = neat.evolve(&rng, &population, &mut history).unwrap();
+
+        for _ in 0..20 {
+            population = neat
+                .evolve(&rng, &population, &mut history)
+                .expect("evolution should conclude successfully")
+                .0;
+
+            assert_eq!(population.len(), 20);
+        }
+
+        let (_, stats) = neat.evolve(&rng, &population, &mut history).unwrap();
+
+        // Every individual of the last population given is in one of the species
+        assert_eq!(
+            neat.species()
+                .iter()
+                .map(|species| species.members().len())
+                .sum::<usize>(),
+            population.len()
+        );
+
+        assert!(stats.avg_fitness() > initial.avg_fitness());
+        assert!(population.iter().any(|individual| individual
+            .genome()
+            .nodes()
+            .iter()
+            .any(|node| node.kind == NodeKind::Hidden)));
+    }
+
+    #[test]
+    fn evolve_no_fitness() {
+        let rng = PetriRand::with_seed(Default::default());
+        let mut history = InnovationHistory::new(1, 1);
+
+        let mut neat = Neat::new(
+            RouletteWheelSelection::new(),
+            GaussianMutation::new(0.5, 0.5),
+            StructuralMutation::new(0.3, 0.3),
+            Speciation::new(3.0),
+        );
+
+        let population = vec![TestGenome::new(
+            Genome::minimal(&rng, &mut history, 1, 1),
+            0.0,
+        )];
+
+        assert!(neat.evolve(&rng, &population, &mut history).is_none());
+    }
+}
@@ -0,0 +1,467 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use petri_rand::PetriRand;
+
+use crate::MutationMethod;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Input,
+    /// Always outputs `1.0`, giving the nodes it connects to an offset.
+    Bias,
+    Output,
+    Hidden,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeGene {
+    pub id: usize,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConnectionGene {
+    /// Identifies the same structural change across every genome, so genes of
+    /// different topologies can be lined up.
+    pub innovation: usize,
+    pub from: usize,
+    pub to: usize,
+    pub weight: f32,
+    pub enabled: bool,
+}
+
+/// Hands out innovation numbers and node ids, so a structural mutation that
+/// happens in several genomes is given the same numbers in all of them. Shared by
+/// a whole population, across generations.
+#[derive(Debug, Clone)]
+pub struct InnovationHistory {
+    next_innovation: usize,
+    next_node: usize,
+    connections: HashMap<(usize, usize), usize>,
+    /// The node created by splitting each connection, by innovation.
+    splits: HashMap<usize, usize>,
+}
+
+impl InnovationHistory {
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        Self {
+            next_innovation: 0,
+            // The inputs, the bias and the outputs take the first ids
+            next_node: inputs + 1 + outputs,
+            connections: HashMap::new(),
+            splits: HashMap::new(),
+        }
+    }
+
+    fn connection(&mut self, from: usize, to: usize) -> usize {
+        let next = &mut self.next_innovation;
+
+        *self.connections.entry((from, to)).or_insert_with(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+
+    fn split(&mut self, innovation: usize) -> usize {
+        let next = &mut self.next_node;
+
+        *self.splits.entry(innovation).or_insert_with(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+}
+
+/// A NEAT genome: a feed-forward graph of nodes and weighted connections that can
+/// grow through structural mutations. Node ids `0..inputs` are the inputs and
+/// `inputs` is the bias, then come the outputs, and hidden nodes after that, the
+/// same as in `petri_nn::GraphNetwork`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Genome {
+    inputs: usize,
+    outputs: usize,
+    nodes: Vec<NodeGene>,
+    /// Kept sorted by innovation number.
+    connections: Vec<ConnectionGene>,
+}
+
+impl Genome {
+    /// Every input and the bias connected to every output with a random weight, and
+    /// nothing else.
+    pub fn minimal(
+        rng: &PetriRand,
+        history: &mut InnovationHistory,
+        inputs: usize,
+        outputs: usize,
+    ) -> Self {
+        let nodes = (0..inputs + 1 + outputs)
+            .map(|id| NodeGene {
+                id,
+                kind: match id.cmp(&inputs) {
+                    Ordering::Less => NodeKind::Input,
+                    Ordering::Equal => NodeKind::Bias,
+                    Ordering::Greater => NodeKind::Output,
+                },
+            })
+            .collect();
+
+        let mut genome = Self {
+            inputs,
+            outputs,
+            nodes,
+            connections: Vec::with_capacity((inputs + 1) * outputs),
+        };
+
+        for from in 0..=inputs {
+            for to in inputs + 1..inputs + 1 + outputs {
+                genome.connect(history, from, to, rng.range_f32(-1.0..1.0));
+            }
+        }
+
+        genome
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    pub fn nodes(&self) -> &[NodeGene] {
+        &self.nodes
+    }
+
+    /// All connection genes by innovation number, including disabled ones.
+    pub fn connections(&self) -> &[ConnectionGene] {
+        &self.connections
+    }
+
+    /// The `(from, to, weight)` of every enabled connection, which is all a network
+    /// needs to be built from the genome.
+    pub fn enabled_connections(&self) -> impl Iterator<Item = (usize, usize, f32)> + '_ {
+        self.connections
+            .iter()
+            .filter(|connection| connection.enabled)
+            .map(|connection| (connection.from, connection.to, connection.weight))
+    }
+
+    /// Perturbs the weight of every connection with `mutation`, as if they were a
+    /// chromosome.
+    pub fn mutate_weights(&mut self, rng: &PetriRand, mutation: &impl MutationMethod) {
+        let weights = mutation.mutate(rng, self.connections.iter().map(|c| c.weight));
+
+        for (connection, weight) in self.connections.iter_mut().zip(weights) {
+            connection.weight = weight;
+        }
+    }
+
+    /// Connects two random unconnected nodes, unless that would make a cycle.
+    /// Returns whether a connection was added.
+    pub fn mutate_add_connection(
+        &mut self,
+        rng: &PetriRand,
+        history: &mut InnovationHistory,
+    ) -> bool {
+        let candidates: Vec<(usize, usize)> = self
+            .nodes
+            .iter()
+            .filter(|from| from.kind != NodeKind::Output)
+            .flat_map(|from| {
+                self.nodes
+                    .iter()
+                    .filter(|to| {
+                        matches!(to.kind, NodeKind::Output | NodeKind::Hidden) && to.id != from.id
+                    })
+                    .map(move |to| (from.id, to.id))
+            })
+            .filter(|&(from, to)| {
+                !self
+                    .connections
+                    .iter()
+                    .any(|connection| connection.from == from && connection.to == to)
+                    && !self.reaches(to, from)
+            })
+            .collect();
+
+        match rng.sample(&candidates) {
+            Some(&(from, to)) => {
+                self.connect(history, from, to, rng.range_f32(-1.0..1.0));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Splits a random enabled connection in two with a new hidden node. The
+    /// connection into the node has a weight of one and the one out of it keeps the
+    /// old weight, so the network behaves about the same as before. Returns whether
+    /// a node was added.
+    pub fn mutate_add_node(&mut self, rng: &PetriRand, history: &mut InnovationHistory) -> bool {
+        let enabled: Vec<usize> = (0..self.connections.len())
+            .filter(|&index| self.connections[index].enabled)
+            .collect();
+
+        let index = match rng.sample(&enabled) {
+            Some(&index) => index,
+            None => return false,
+        };
+
+        let split = self.connections[index];
+        let node = history.split(split.innovation);
+
+        // The same connection was split before, then re-enabled by a crossover
+        if self.nodes.iter().any(|n| n.id == node) {
+            return false;
+        }
+
+        self.connections[index].enabled = false;
+        self.nodes.push(NodeGene {
+            id: node,
+            kind: NodeKind::Hidden,
+        });
+        self.connect(history, split.from, node, 1.0);
+        self.connect(history, node, split.to, split.weight);
+
+        true
+    }
+
+    /// Combines two parents, lining their genes up by innovation number. Matching
+    /// genes come from either parent at random, while the genes only one parent has
+    /// are taken from `fitter` alone, so the child keeps its structure. A gene that
+    /// is disabled in either parent is likely to stay disabled.
+    pub fn crossover(rng: &PetriRand, fitter: &Genome, other: &Genome) -> Genome {
+        let connections = fitter
+            .connections
+            .iter()
+            .map(|&gene| match other.connection(gene.innovation) {
+                Some(matching) => {
+                    let mut child = if rng.bool() { gene } else { *matching };
+
+                    child.enabled = (gene.enabled && matching.enabled) || rng.chance(0.25);
+                    child
+                }
+                None => gene,
+            })
+            .collect();
+
+        Genome {
+            inputs: fitter.inputs,
+            outputs: fitter.outputs,
+            nodes: fitter.nodes.clone(),
+            connections,
+        }
+    }
+
+    pub(crate) fn connection(&self, innovation: usize) -> Option<&ConnectionGene> {
+        self.connections
+            .binary_search_by_key(&innovation, |connection| connection.innovation)
+            .ok()
+            .map(|index| &self.connections[index])
+    }
+
+    fn connect(&mut self, history: &mut InnovationHistory, from: usize, to: usize, weight: f32) {
+        let innovation = history.connection(from, to);
+
+        let index = self
+            .connections
+            .binary_search_by_key(&innovation, |connection| connection.innovation)
+            .unwrap_or_else(|index| index);
+
+        self.connections.insert(
+            index,
+            ConnectionGene {
+                innovation,
+                from,
+                to,
+                weight,
+                enabled: true,
+            },
+        );
+    }
+
+    /// Whether `target` can be reached from `start` by following connections,
+    /// disabled ones included, since crossover can enable them again.
+    fn reaches(&self, start: usize, target: usize) -> bool {
+        let mut stack = vec![start];
+        let mut visited = vec![start];
+
+        while let Some(node) = stack.pop() {
+            if node == target {
+                return true;
+            }
+
+            for connection in self.connections.iter().filter(|c| c.from == node) {
+                if !visited.contains(&connection.to) {
+                    visited.push(connection.to);
+                    stack.push(connection.to);
+                }
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GaussianMutation;
+    use approx::assert_relative_eq;
+    use petri_nn::{Activation, GraphNetwork};
+
+    fn minimal() -> (PetriRand, InnovationHistory, Genome) {
+        let rng = PetriRand::with_seed(Default::default());
+        let mut history = InnovationHistory::new(2, 1);
+        let genome = Genome::minimal(&rng, &mut history, 2, 1);
+
+        (rng, history, genome)
+    }
+
+    #[test]
+    fn minimal_genome() {
+        let (_, _, genome) = minimal();
+
+        assert_eq!(genome.nodes().len(), 4);
+        assert_eq!(genome.nodes()[2].kind, NodeKind::Bias);
+        assert_eq!(
+            genome
+                .enabled_connections()
+                .map(|(from, to, _)| (from, to))
+                .collect::<Vec<_>>(),
+            vec![(0, 3), (1, 3), (2, 3)]
+        );
+    }
+
+    #[test]
+    fn add_node() {
+        let (rng, mut history, mut genome) = minimal();
+        let mut other = genome.clone();
+
+        assert!(genome.mutate_add_node(&rng, &mut history));
+
+        let split = genome.connections().iter().find(|c| !c.enabled).unwrap();
+
+        assert_eq!(genome.nodes()[4].kind, NodeKind::Hidden);
+        assert_eq!(genome.connections().len(), 5);
+        assert!(genome
+            .enabled_connections()
+            .any(|(from, to, weight)| from == split.from && to == 4 && weight == 1.0));
+        assert!(genome
+            .enabled_connections()
+            .any(|(from, to, weight)| from == 4 && to == split.to && weight == split.weight));
+
+        // The same split in another genome gets the same node and innovations
+        other
+            .connections
+            .iter_mut()
+            .for_each(|c| c.enabled = c.innovation == split.innovation);
+        assert!(other.mutate_add_node(&rng, &mut history));
+        assert_eq!(other.nodes(), genome.nodes());
+        assert!(other
+            .connections()
+            .iter()
+            .zip(genome.connections())
+            .all(|(a, b)| a.innovation == b.innovation));
+    }
+
+    #[test]
+    fn add_connection() {
+        let (rng, mut history, mut genome) = minimal();
+
+        // Fully connected, so nothing left to add
+        assert!(!genome.mutate_add_connection(&rng, &mut history));
+
+        genome.mutate_add_node(&rng, &mut history);
+
+        // The only new options are the two of the inputs and bias not yet going into
+        // the hidden node
+        assert!(genome.mutate_add_connection(&rng, &mut history));
+        assert!(genome.mutate_add_connection(&rng, &mut history));
+        assert_eq!(genome.connections().len(), 7);
+        assert!(!genome.mutate_add_connection(&rng, &mut history));
+
+        // Connections stay sorted by innovation
+        assert!(genome
+            .connections()
+            .windows(2)
+            .all(|pair| pair[0].innovation < pair[1].innovation));
+    }
+
+    #[test]
+    fn crossover() {
+        let (rng, mut history, mut fitter) = minimal();
+        let mut other = fitter.clone();
+
+        other.mutate_weights(&rng, &GaussianMutation::new(1.0, 0.5));
+        other.mutate_add_node(&rng, &mut history);
+        fitter.mutate_add_node(&rng, &mut history);
+        fitter.mutate_add_connection(&rng, &mut history);
+
+        for _ in 0..10 {
+            let child = Genome::crossover(&rng, &fitter, &other);
+
+            // The structure always comes from the fitter parent
+            assert_eq!(child.nodes(), fitter.nodes());
+            assert!(child
+                .connections()
+                .iter()
+                .zip(fitter.connections())
+                .all(|(c, f)| (c.innovation, c.from, c.to) == (f.innovation, f.from, f.to)));
+
+            // Matching weights come from either parent
+            for gene in child.connections() {
+                let weights = [
+                    fitter.connection(gene.innovation),
+                    other.connection(gene.innovation),
+                ];
+
+                assert!(weights
+                    .iter()
+                    .flatten()
+                    .any(|parent| parent.weight == gene.weight));
+            }
+        }
+    }
+
+    #[test]
+    fn network() {
+        let (rng, mut history, mut genome) = minimal();
+        genome.mutate_add_node(&rng, &mut history);
+        genome.mutate_add_connection(&rng, &mut history);
+
+        let network = GraphNetwork::new(
+            genome.inputs(),
+            genome.outputs(),
+            Activation::Identity,
+            genome.enabled_connections(),
+        );
+
+        // Evaluates the genome directly, following its enabled connections back
+        fn value(genome: &Genome, inputs: &[f32], node: usize) -> f32 {
+            match node.cmp(&genome.inputs()) {
+                Ordering::Less => inputs[node],
+                Ordering::Equal => 1.0,
+                Ordering::Greater => genome
+                    .enabled_connections()
+                    .filter(|&(_, to, _)| to == node)
+                    .map(|(from, _, weight)| value(genome, inputs, from) * weight)
+                    .sum(),
+            }
+        }
+
+        for inputs in [[0.0, 0.0], [0.3, -0.7]] {
+            let expected = value(&genome, &inputs, genome.inputs() + 1);
+
+            assert_relative_eq!(
+                network.propagate(inputs.to_vec())[0],
+                expected,
+                epsilon = 1e-6
+            );
+        }
+
+        // The bias gives an offset even without any input
+        assert!(network.propagate(vec![0.0, 0.0])[0].abs() > 0.0);
+    }
+}
@@ -0,0 +1,272 @@
+use petri_rand::PetriRand;
+
+use super::{Genome, NeatIndividual};
+
+/// A species as it carries over from one generation to the next. New genomes are
+/// compared against its representative, one of its members from the previous
+/// generation.
+#[derive(Debug, Clone)]
+pub struct Species {
+    representative: Genome,
+    /// Indices into the population the species was last speciated from.
+    members: Vec<usize>,
+}
+
+impl Species {
+    pub fn representative(&self) -> &Genome {
+        &self.representative
+    }
+
+    pub fn members(&self) -> &[usize] {
+        &self.members
+    }
+}
+
+/// Groups genomes into species by how similar their structure and weights are,
+/// so that new structures only compete with their own kind until their weights
+/// have had a chance to be tuned.
+#[derive(Debug, Clone)]
+pub struct Speciation {
+    /// Genomes closer than this belong to the same species.
+    threshold: f32,
+    excess: f32,
+    disjoint: f32,
+    weight: f32,
+}
+
+impl Speciation {
+    /// Uses the coefficients of the original NEAT paper: excess and disjoint genes
+    /// weigh `1.0`, the average weight difference of matching genes `0.4`.
+    pub fn new(threshold: f32) -> Self {
+        Self::with_coefficients(threshold, 1.0, 1.0, 0.4)
+    }
+
+    pub fn with_coefficients(threshold: f32, excess: f32, disjoint: f32, weight: f32) -> Self {
+        debug_assert!(threshold > 0.0);
+
+        Self {
+            threshold,
+            excess,
+            disjoint,
+            weight,
+        }
+    }
+
+    /// Compatibility distance between two genomes, from the number of excess genes
+    /// (beyond the other genome's last innovation), disjoint genes (any other gene
+    /// only one has) and the average weight difference of matching genes.
+    pub fn distance(&self, a: &Genome, b: &Genome) -> f32 {
+        let (a, b) = (a.connections(), b.connections());
+
+        let (mut i, mut j) = (0, 0);
+        let (mut disjoint, mut matching, mut weight_difference) = (0, 0, 0.0);
+
+        while i < a.len() && j < b.len() {
+            match a[i].innovation.cmp(&b[j].innovation) {
+                std::cmp::Ordering::Equal => {
+                    matching += 1;
+                    weight_difference += (a[i].weight - b[j].weight).abs();
+                    i += 1;
+                    j += 1;
+                }
+                std::cmp::Ordering::Less => {
+                    disjoint += 1;
+                    i += 1;
+                }
+                std::cmp::Ordering::Greater => {
+                    disjoint += 1;
+                    j += 1;
+                }
+            }
+        }
+
+        let excess = (a.len() - i) + (b.len() - j);
+
+        // Small genomes aren't normalised, as in the original paper
+        let size = match a.len().max(b.len()) {
+            size if size < 20 => 1.0,
+            size => size as f32,
+        };
+
+        let weight_difference = match matching {
+            0 => 0.0,
+            matching => weight_difference / matching as f32,
+        };
+
+        self.excess * excess as f32 / size
+            + self.disjoint * disjoint as f32 / size
+            + self.weight * weight_difference
+    }
+
+    /// Sorts a population into the species of earlier generations. Each genome joins
+    /// the first species whose representative it is close enough to, or starts a new
+    /// one. Species left without members die out, and the rest pick a random member
+    /// as the representative for the next generation.
+    pub fn speciate<I: NeatIndividual>(
+        &self,
+        rng: &PetriRand,
+        species: &mut Vec<Species>,
+        population: &[I],
+    ) {
+        for species in species.iter_mut() {
+            species.members.clear();
+        }
+
+        for (index, individual) in population.iter().enumerate() {
+            let genome = individual.genome();
+
+            let found = species
+                .iter_mut()
+                .find(|species| self.distance(&species.representative, genome) < self.threshold);
+
+            match found {
+                Some(species) => species.members.push(index),
+                None => species.push(Species {
+                    representative: genome.clone(),
+                    members: vec![index],
+                }),
+            }
+        }
+
+        species.retain(|species| !species.members.is_empty());
+
+        for species in species.iter_mut() {
+            let representative = *rng.sample(&species.members).unwrap();
+            species.representative = population[representative].genome().clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::{InnovationHistory, TestGenome};
+    use approx::assert_relative_eq;
+    use petri_rand::PetriRand;
+
+    #[test]
+    fn distance() {
+        let rng = PetriRand::with_seed(Default::default());
+        let mut history = InnovationHistory::new(2, 1);
+        let a = Genome::minimal(&rng, &mut history, 2, 1);
+        let mut b = a.clone();
+
+        let speciation = Speciation::new(3.0);
+
+        assert_relative_eq!(speciation.distance(&a, &b), 0.0);
+
+        // Splitting a connection adds two excess genes
+        b.mutate_add_node(&rng, &mut history);
+
+        assert_relative_eq!(speciation.distance(&a, &b), 2.0);
+
+        // Another split in a different genome makes the first two disjoint
+        let split = |genome: &Genome| genome.connections().iter().position(|c| !c.enabled);
+        let c = loop {
+            let mut c = a.clone();
+            c.mutate_add_node(&rng, &mut history);
+
+            if split(&c) != split(&b) {
+                break c;
+            }
+        };
+
+        let weights: f32 = b
+            .connections()
+            .iter()
+            .filter_map(|gene| {
+                c.connection(gene.innovation)
+                    .map(|m| (gene.weight - m.weight).abs())
+            })
+            .sum();
+        let matching = b
+            .connections()
+            .iter()
+            .filter(|g| c.connection(g.innovation).is_some())
+            .count();
+
+        let distance = speciation.distance(&b, &c);
+
+        assert!(distance >= 2.0);
+        assert_relative_eq!(
+            distance,
+            (b.connections().len() + c.connections().len() - 2 * matching) as f32
+                + 0.4 * weights / matching as f32
+        );
+    }
+
+    #[test]
+    fn speciate() {
+        let rng = PetriRand::with_seed(Default::default());
+        let mut history = InnovationHistory::new(2, 1);
+        let a = Genome::minimal(&rng, &mut history, 2, 1);
+        let mut b = a.clone();
+        b.mutate_add_node(&rng, &mut history);
+        b.mutate_add_node(&rng, &mut history);
+
+        let population = vec![
+            TestGenome::new(a.clone(), 1.0),
+            TestGenome::new(b.clone(), 1.0),
+            TestGenome::new(a.clone(), 1.0),
+            TestGenome::new(b.clone(), 1.0),
+        ];
+
+        let members = |species: &[Species]| -> Vec<Vec<usize>> {
+            species.iter().map(|s| s.members().to_vec()).collect()
+        };
+
+        let mut species = Vec::new();
+        Speciation::new(1.0).speciate(&rng, &mut species, &population);
+
+        assert_eq!(members(&species), vec![vec![0, 2], vec![1, 3]]);
+
+        let mut species = Vec::new();
+        Speciation::new(10.0).speciate(&rng, &mut species, &population);
+
+        assert_eq!(members(&species), vec![vec![0, 1, 2, 3]]);
+    }
+
+    #[test]
+    fn species_carry_over() {
+        let rng = PetriRand::with_seed(Default::default());
+        let mut history = InnovationHistory::new(2, 1);
+        let a = Genome::minimal(&rng, &mut history, 2, 1);
+        let mut b = a.clone();
+        b.mutate_add_node(&rng, &mut history);
+        b.mutate_add_node(&rng, &mut history);
+
+        let speciation = Speciation::new(1.0);
+        let mut species = Vec::new();
+
+        speciation.speciate(
+            &rng,
+            &mut species,
+            &[
+                TestGenome::new(a.clone(), 1.0),
+                TestGenome::new(b.clone(), 1.0),
+            ],
+        );
+
+        // Species keep their order, whichever genome comes first in the next generation
+        speciation.speciate(
+            &rng,
+            &mut species,
+            &[
+                TestGenome::new(b.clone(), 1.0),
+                TestGenome::new(a.clone(), 1.0),
+                TestGenome::new(b.clone(), 1.0),
+            ],
+        );
+
+        assert_eq!(species.len(), 2);
+        assert_eq!(species[0].members(), [1]);
+        assert_eq!(species[1].members(), [0, 2]);
+        assert_eq!(species[1].representative(), &b);
+
+        // A species without members dies out
+        speciation.speciate(&rng, &mut species, &[TestGenome::new(b.clone(), 1.0)]);
+
+        assert_eq!(species.len(), 1);
+        assert_eq!(species[0].representative(), &b);
+    }
+}
@@ -1,12 +1,12 @@
 use petri_rand::PetriRand;
 use std::iter::repeat_with;
 
-use crate::individual::Individual;
+use crate::individual::Candidate;
 
 pub trait SelectionMethod {
     fn select<'a, I, F>(&self, rng: &PetriRand, population: &'a [I], selection_chance: F) -> Option<&'a I>
     where
-        I: Individual,
+        I: Candidate,
         F: Fn(&I) -> f32;
 }
 
@@ -28,7 +28,7 @@ impl Default for RouletteWheelSelection {
 impl SelectionMethod for RouletteWheelSelection {
     fn select<'a, I, F>(&self, rng: &PetriRand, population: &'a [I], selection_chance: F) -> Option<&'a I>
     where
-        I: Individual,
+        I: Candidate,
         F: Fn(&I) -> f32,
     {
         repeat_with(|| rng.sample(population))
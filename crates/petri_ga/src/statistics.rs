@@ -1,4 +1,4 @@
-use crate::Individual;
+use crate::Candidate;
 
 #[derive(Debug, Clone)]
 pub struct StatisticsBuilder {
@@ -21,7 +21,7 @@ impl StatisticsBuilder {
     #[must_use]
     pub fn add_sample<I>(mut self, individual: &I) -> Self
     where
-        I: Individual,
+        I: Candidate,
     {
         let fitness = individual.fitness();
 
@@ -35,7 +35,7 @@ impl StatisticsBuilder {
 
     pub fn from_population<I>(population: &[I]) -> Statistics
     where
-        I: Individual,
+        I: Candidate,
     {
         population
             .iter()
@@ -9,6 +9,16 @@ pub fn init_benchmark(c: &mut Criterion) {
         let topology = vec![10, 30, 10, 5];
         b.iter(|| Network::random(&rng, black_box(topology.clone())));
     });
+    c.bench_function("petri-nn random 100/300/100/50", |b| {
+        let rng = PetriRand::new();
+        let topology = vec![100, 300, 100, 50];
+        b.iter(|| Network::random(&rng, black_box(topology.clone())));
+    });
+    c.bench_function("petri-nn random 500/1000/500", |b| {
+        let rng = PetriRand::new();
+        let topology = vec![500, 1000, 500];
+        b.iter(|| Network::random(&rng, black_box(topology.clone())));
+    });
 }
 
 pub fn propagate_benchmark(c: &mut Criterion) {
@@ -32,6 +42,61 @@ pub fn propagate_benchmark(c: &mut Criterion) {
     });
 }
 
+pub fn propagate_into_benchmark(c: &mut Criterion) {
+    c.bench_function("petri-nn propagate_into 3/2/1", |b| {
+        let rng = PetriRand::new();
+        let inputs: Vec<_> = repeat_with(|| rng.get_f32_normalised()).take(3).collect();
+        let nn = Network::random(&rng, vec![3, 2, 1]);
+        let mut scratch = nn.scratch();
+        b.iter(|| nn.propagate_into(black_box(&inputs), &mut scratch)[0]);
+    });
+    c.bench_function("petri-nn propagate_into 15/30/5", |b| {
+        let rng = PetriRand::new();
+        let inputs: Vec<_> = repeat_with(|| rng.get_f32_normalised()).take(15).collect();
+        let nn = Network::random(&rng, vec![15, 30, 5]);
+        let mut scratch = nn.scratch();
+        b.iter(|| nn.propagate_into(black_box(&inputs), &mut scratch)[0]);
+    });
+    c.bench_function("petri-nn propagate_into 10/20/10/5/2", |b| {
+        let rng = PetriRand::new();
+        let inputs: Vec<_> = repeat_with(|| rng.get_f32_normalised()).take(10).collect();
+        let nn = Network::random(&rng, vec![10, 20, 10, 5, 2]);
+        let mut scratch = nn.scratch();
+        b.iter(|| nn.propagate_into(black_box(&inputs), &mut scratch)[0]);
+    });
+    c.bench_function("petri-nn propagate_into 100/300/100/50", |b| {
+        let rng = PetriRand::new();
+        let inputs: Vec<_> = repeat_with(|| rng.get_f32_normalised()).take(100).collect();
+        let nn = Network::random(&rng, vec![100, 300, 100, 50]);
+        let mut scratch = nn.scratch();
+        b.iter(|| nn.propagate_into(black_box(&inputs), &mut scratch)[0]);
+    });
+}
+
+pub fn batch_benchmark(c: &mut Criterion) {
+    let rng = PetriRand::new();
+    let networks: Vec<_> = repeat_with(|| Network::random(&rng, vec![11, 22, 11, 6, 3]))
+        .take(1000)
+        .collect();
+    let inputs: Vec<Vec<f32>> =
+        repeat_with(|| repeat_with(|| rng.get_f32_normalised()).take(11).collect())
+            .take(networks.len())
+            .collect();
+
+    c.bench_function("petri-nn propagate_into 1000x 11/22/11/6/3", |b| {
+        let mut scratch = Scratch::new();
+        b.iter(|| {
+            for (network, inputs) in networks.iter().zip(&inputs) {
+                black_box(network.propagate_into(black_box(inputs), &mut scratch)[0]);
+            }
+        });
+    });
+    c.bench_function("petri-nn batch 1000x 11/22/11/6/3", |b| {
+        let batch = NetworkBatch::new(&networks);
+        b.iter(|| batch.propagate(black_box(&inputs)));
+    });
+}
+
 pub fn weights_benchmark(c: &mut Criterion) {
     c.bench_function("petri-nn weights 10/2/1", |b| {
         let rng = PetriRand::new();
@@ -48,5 +113,12 @@ pub fn weights_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, propagate_benchmark, weights_benchmark, init_benchmark);
+criterion_group!(
+    benches,
+    propagate_benchmark,
+    propagate_into_benchmark,
+    batch_benchmark,
+    weights_benchmark,
+    init_benchmark
+);
 criterion_main!(benches);
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+/// Activation function applied to the weighted sum of a neuron's inputs. Every
+/// neuron of a layer shares the same activation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Activation {
+    Sigmoid,
+    Tanh,
+    Relu,
+    LeakyRelu,
+    /// Leaky ReLU with a learned slope: each neuron's coefficient.
+    #[default]
+    PRelu,
+    Softsign,
+    Gaussian,
+    Sine,
+    Identity,
+}
+
+impl Activation {
+    pub const ALL: [Activation; 9] = [
+        Self::Sigmoid,
+        Self::Tanh,
+        Self::Relu,
+        Self::LeakyRelu,
+        Self::PRelu,
+        Self::Softsign,
+        Self::Gaussian,
+        Self::Sine,
+        Self::Identity,
+    ];
+
+    const LEAKY_SLOPE: f32 = 0.01;
+
+    /// Applies the activation to `x`. `coefficient` is only used by `PRelu`.
+    #[inline]
+    pub fn apply(self, x: f32, coefficient: f32) -> f32 {
+        match self {
+            Self::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Self::Tanh => x.tanh(),
+            Self::Relu => x.max(0.0),
+            Self::LeakyRelu if x > 0.0 => x,
+            Self::LeakyRelu => Self::LEAKY_SLOPE * x,
+            Self::PRelu if x > 0.0 => x,
+            Self::PRelu => coefficient * x,
+            Self::Softsign => x / (1.0 + x.abs()),
+            Self::Gaussian => (-x * x).exp(),
+            Self::Sine => x.sin(),
+            Self::Identity => x,
+        }
+    }
+
+    /// The derivative of `apply` with respect to `x`. At the kinks of the ReLUs, it
+    /// is the slope to the left.
+    #[inline]
+    pub fn derivative(self, x: f32, coefficient: f32) -> f32 {
+        match self {
+            Self::Sigmoid => {
+                let y = self.apply(x, coefficient);
+                y * (1.0 - y)
+            }
+            Self::Tanh => 1.0 - x.tanh().powi(2),
+            Self::Relu if x > 0.0 => 1.0,
+            Self::Relu => 0.0,
+            Self::LeakyRelu if x > 0.0 => 1.0,
+            Self::LeakyRelu => Self::LEAKY_SLOPE,
+            Self::PRelu if x > 0.0 => 1.0,
+            Self::PRelu => coefficient,
+            Self::Softsign => 1.0 / (1.0 + x.abs()).powi(2),
+            Self::Gaussian => -2.0 * x * (-x * x).exp(),
+            Self::Sine => x.cos(),
+            Self::Identity => 1.0,
+        }
+    }
+
+    /// The derivative of `apply` with respect to `coefficient`, which is zero for
+    /// every activation but `PRelu`.
+    #[inline]
+    pub fn coefficient_derivative(self, x: f32) -> f32 {
+        match self {
+            Self::PRelu if x <= 0.0 => x,
+            _ => 0.0,
+        }
+    }
+
+    /// Encodes the activation as a gene, in the middle of its `1 / ALL.len()` wide
+    /// slot of `[0.0, 1.0)`, so small mutations don't immediately change it.
+    pub fn to_gene(self) -> f32 {
+        let index = Self::ALL.iter().position(|&a| a == self).unwrap();
+
+        (index as f32 + 0.5) / Self::ALL.len() as f32
+    }
+
+    /// Decodes a gene produced by `to_gene`. Genes mutated out of `[0.0, 1.0)`
+    /// wrap around, so any value maps to some activation.
+    pub fn from_gene(gene: f32) -> Self {
+        let slots = Self::ALL.len();
+        let index = (gene.rem_euclid(1.0) * slots as f32) as usize;
+
+        Self::ALL[index.min(slots - 1)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::*;
+
+    #[test]
+    fn apply() {
+        assert_relative_eq!(Activation::Sigmoid.apply(0.0, 0.1), 0.5);
+        assert_relative_eq!(Activation::Tanh.apply(1.0, 0.1), 1.0f32.tanh());
+        assert_relative_eq!(Activation::Relu.apply(-2.0, 0.1), 0.0);
+        assert_relative_eq!(Activation::Relu.apply(2.0, 0.1), 2.0);
+        assert_relative_eq!(Activation::LeakyRelu.apply(-2.0, 0.1), -0.02);
+        assert_relative_eq!(Activation::PRelu.apply(-2.0, 0.1), -0.2);
+        assert_relative_eq!(Activation::PRelu.apply(2.0, 0.1), 2.0);
+        assert_relative_eq!(Activation::Softsign.apply(-3.0, 0.1), -0.75);
+        assert_relative_eq!(Activation::Gaussian.apply(0.0, 0.1), 1.0);
+        assert_relative_eq!(Activation::Sine.apply(0.5, 0.1), 0.5f32.sin());
+        assert_relative_eq!(Activation::Identity.apply(-7.5, 0.1), -7.5);
+    }
+
+    #[test]
+    fn derivative() {
+        let h = 1e-3;
+
+        // Away from the kinks, so the finite differences hold
+        for activation in Activation::ALL {
+            for x in [-1.3, -0.4, 0.3, 0.9, 2.1] {
+                let numeric =
+                    (activation.apply(x + h, 0.2) - activation.apply(x - h, 0.2)) / (2.0 * h);
+
+                assert_relative_eq!(activation.derivative(x, 0.2), numeric, epsilon = 1e-2);
+            }
+        }
+
+        assert_relative_eq!(Activation::PRelu.coefficient_derivative(-2.0), -2.0);
+        assert_relative_eq!(Activation::PRelu.coefficient_derivative(2.0), 0.0);
+        assert_relative_eq!(Activation::Tanh.coefficient_derivative(-2.0), 0.0);
+    }
+
+    #[test]
+    fn genes() {
+        for activation in Activation::ALL {
+            assert_eq!(Activation::from_gene(activation.to_gene()), activation);
+            // Mutations of up to half a slot keep the activation
+            assert_eq!(
+                Activation::from_gene(activation.to_gene() + 0.05),
+                activation
+            );
+        }
+
+        assert_eq!(Activation::from_gene(-0.01), Activation::Identity);
+        assert_eq!(Activation::from_gene(1.01), Activation::Sigmoid);
+    }
+}
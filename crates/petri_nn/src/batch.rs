@@ -0,0 +1,384 @@
+use crate::{
+    kernel, parameters::LayerParameters, Activation, HiddenState, LayerKind, Layout, Network,
+    Source, Topology,
+};
+
+/// Many networks of the same topology, evaluated together. Their parameters are
+/// interleaved as structure of arrays, with the values of every network for the
+/// same weight side by side, so each step of a propagation runs across the whole
+/// batch at once. Activations can still differ between the networks.
+///
+/// The batch is a copy, so it has to be rebuilt when the networks change.
+#[derive(Debug, Clone)]
+pub struct NetworkBatch {
+    topology: Topology,
+    layout: Layout,
+    networks: usize,
+    layers: Vec<BatchLayer>,
+}
+
+#[derive(Debug, Clone)]
+struct BatchLayer {
+    /// Indexed by neuron, then network.
+    biases: Vec<f32>,
+    /// Indexed by neuron, then network.
+    coefficients: Vec<f32>,
+    /// Indexed by neuron, then input, then network.
+    weights: Vec<f32>,
+    /// The activation of each network.
+    activations: Vec<Activation>,
+    /// The activation of every network, when they all have the same one.
+    shared: Option<Activation>,
+}
+
+impl NetworkBatch {
+    /// Panics when the networks don't all share a topology. Without any networks,
+    /// the batch is empty and only propagates empty lists of inputs.
+    pub fn new<'a>(networks: impl IntoIterator<Item = &'a Network>) -> Self {
+        let networks: Vec<&Network> = networks.into_iter().collect();
+
+        let first = match networks.first() {
+            Some(first) => first,
+            None => {
+                let topology = Topology::dense(&[]);
+
+                return Self {
+                    layout: Layout::new(&topology),
+                    topology,
+                    networks: 0,
+                    layers: Vec::new(),
+                };
+            }
+        };
+
+        assert!(
+            networks
+                .iter()
+                .all(|network| network.topology.same_shape(&first.topology)),
+            "Networks must share a topology"
+        );
+
+        let topology = &first.topology;
+
+        let layers = topology
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| {
+                let inputs = topology.neuron_inputs(index);
+                let parameters: Vec<&LayerParameters> = networks
+                    .iter()
+                    .map(|network| &network.layers[index])
+                    .collect();
+                let parameters = &parameters;
+
+                let interleave = |value: fn(&LayerParameters, usize) -> f32| -> Vec<f32> {
+                    (0..layer.size)
+                        .flat_map(|neuron| parameters.iter().map(move |p| value(p, neuron)))
+                        .collect()
+                };
+
+                let activations: Vec<Activation> = networks
+                    .iter()
+                    .map(|network| network.topology.layers[index].activation)
+                    .collect();
+
+                BatchLayer {
+                    biases: interleave(|p, neuron| p.biases()[neuron]),
+                    coefficients: interleave(|p, neuron| p.coefficients()[neuron]),
+                    weights: (0..layer.size)
+                        .flat_map(|neuron| {
+                            (0..inputs).flat_map(move |input| {
+                                parameters.iter().map(move |p| p.row(neuron)[input])
+                            })
+                        })
+                        .collect(),
+                    shared: activations
+                        .iter()
+                        .all(|&activation| activation == activations[0])
+                        .then_some(activations[0]),
+                    activations,
+                }
+            })
+            .collect();
+
+        Self {
+            topology: topology.clone(),
+            layout: first.layout.clone(),
+            networks: networks.len(),
+            layers,
+        }
+    }
+
+    /// The number of networks in the batch.
+    pub fn len(&self) -> usize {
+        self.networks
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Propagates the concatenated input heads of each network, in the order the
+    /// networks were given, returning their concatenated output heads. Recurrent
+    /// layers start from a zeroed hidden state every time.
+    pub fn propagate(&self, inputs: &[impl AsRef<[f32]>]) -> Vec<Vec<f32>> {
+        self.evaluate(inputs, None)
+    }
+
+    /// Like `propagate`, but recurrent layers carry their outputs over to the next
+    /// call through the `states`, one per network.
+    pub fn propagate_with_state(
+        &self,
+        inputs: &[impl AsRef<[f32]>],
+        states: &mut [HiddenState],
+    ) -> Vec<Vec<f32>> {
+        assert!(
+            states.len() == self.networks,
+            "Expected one state per network"
+        );
+
+        self.evaluate(inputs, Some(states))
+    }
+
+    fn evaluate(
+        &self,
+        inputs: &[impl AsRef<[f32]>],
+        mut states: Option<&mut [HiddenState]>,
+    ) -> Vec<Vec<f32>> {
+        assert!(
+            inputs.len() == self.networks,
+            "Expected inputs for every network"
+        );
+
+        let networks = self.networks;
+        let topology = &self.topology;
+        let layout = &self.layout;
+
+        // Interleave the inputs the same way as the parameters
+        let mut batch_inputs = vec![0.0; topology.input_size() * networks];
+
+        for (network, inputs) in inputs.iter().enumerate() {
+            let inputs = inputs.as_ref();
+            debug_assert!(inputs.len() == topology.input_size());
+
+            for (input, &value) in inputs.iter().enumerate() {
+                batch_inputs[input * networks + network] = value;
+            }
+        }
+
+        let mut values = vec![0.0; layout.layers[topology.layers.len()] * networks];
+        let mut gathered = vec![0.0; layout.gathered * networks];
+
+        for (index, (layer, batch)) in topology.layers.iter().zip(&self.layers).enumerate() {
+            let (finished, rest) = values.split_at_mut(layout.layers[index] * networks);
+            let outputs = &mut rest[..layer.size * networks];
+            let finished = &*finished;
+
+            let source = |source: &Source| match *source {
+                Source::Input(input) => {
+                    &batch_inputs[layout.inputs[input] * networks..]
+                        [..topology.inputs[input] * networks]
+                }
+                Source::Layer(earlier) => {
+                    &finished[layout.layers[earlier] * networks..]
+                        [..topology.layers[earlier].size * networks]
+                }
+            };
+
+            let layer_inputs = match (layer.sources.as_slice(), layer.kind) {
+                ([single], LayerKind::Dense) => source(single),
+                (sources, kind) => {
+                    let mut len = 0;
+
+                    for values in sources.iter().map(source) {
+                        gathered[len..][..values.len()].copy_from_slice(values);
+                        len += values.len();
+                    }
+
+                    if kind == LayerKind::Elman {
+                        let recurrent = &mut gathered[len..][..layer.size * networks];
+
+                        match states.as_deref() {
+                            Some(states) => {
+                                for (network, state) in states.iter().enumerate() {
+                                    for (neuron, &value) in state.layer(index).iter().enumerate() {
+                                        recurrent[neuron * networks + network] = value;
+                                    }
+                                }
+                            }
+                            None => recurrent.fill(0.0),
+                        }
+
+                        len += layer.size * networks;
+                    }
+
+                    &gathered[..len]
+                }
+            };
+
+            kernel::dense_batch(
+                &batch.weights,
+                &batch.biases,
+                layer_inputs,
+                outputs,
+                networks,
+            );
+
+            for (outputs, coefficients) in outputs
+                .chunks_exact_mut(networks)
+                .zip(batch.coefficients.chunks_exact(networks))
+            {
+                match batch.shared {
+                    Some(activation) => kernel::activate(activation, outputs, coefficients),
+                    None => {
+                        for ((output, activation), &coefficient) in
+                            outputs.iter_mut().zip(&batch.activations).zip(coefficients)
+                        {
+                            *output = activation.apply(*output, coefficient);
+                        }
+                    }
+                }
+            }
+
+            if let (LayerKind::Elman, Some(states)) = (layer.kind, states.as_deref_mut()) {
+                for (network, state) in states.iter_mut().enumerate() {
+                    for (neuron, value) in state.layer_mut(index).iter_mut().enumerate() {
+                        *value = outputs[neuron * networks + network];
+                    }
+                }
+            }
+        }
+
+        // Pull each network's output heads back out of the interleaved values
+        (0..networks)
+            .map(|network| {
+                topology
+                    .outputs
+                    .iter()
+                    .flat_map(|&output| {
+                        values[layout.layers[output] * networks..]
+                            [..topology.layers[output].size * networks]
+                            .iter()
+                            .skip(network)
+                            .step_by(networks)
+                    })
+                    .copied()
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::*;
+    use petri_rand::*;
+
+    fn topology() -> Topology {
+        let mut topology = Topology::dense(&[3, 5, 4, 2]);
+        topology.layers[1].kind = LayerKind::Elman;
+        topology.layers[2].sources.push(Source::Input(0));
+        topology.outputs = vec![2, 0];
+        topology
+    }
+
+    #[test]
+    fn propagate() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let networks: Vec<Network> = Activation::ALL
+            .iter()
+            .map(|&activation| {
+                Network::random(&rng, topology()).with_activations(vec![activation; 3])
+            })
+            .collect();
+
+        let batch = NetworkBatch::new(&networks);
+        let inputs: Vec<Vec<f32>> = networks
+            .iter()
+            .map(|_| vec![rng.get_f32(), rng.get_f32(), rng.get_f32()])
+            .collect();
+
+        assert_eq!(batch.len(), networks.len());
+
+        for (network, (inputs, outputs)) in networks
+            .iter()
+            .zip(inputs.iter().zip(batch.propagate(&inputs)))
+        {
+            let expected = network.propagate(inputs.clone());
+
+            assert_relative_eq!(outputs.as_slice(), expected.as_slice(), epsilon = 1e-5);
+        }
+
+        // The same again when every network shares an activation
+        let networks = vec![networks[0].clone(), networks[0].clone()];
+        let batch = NetworkBatch::new(&networks);
+
+        let outputs = batch.propagate(&[[0.1, 0.2, 0.3], [-0.3, -0.2, -0.1]]);
+        let expected = networks[1].propagate(vec![-0.3, -0.2, -0.1]);
+
+        assert_relative_eq!(outputs[1].as_slice(), expected.as_slice(), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn propagate_with_state() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let networks: Vec<Network> = (0..4).map(|_| Network::random(&rng, topology())).collect();
+
+        let batch = NetworkBatch::new(&networks);
+        let mut states: Vec<HiddenState> = networks.iter().map(Network::hidden_state).collect();
+        let mut expected_states = states.clone();
+
+        for _ in 0..3 {
+            let inputs: Vec<[f32; 3]> = networks
+                .iter()
+                .map(|_| [rng.get_f32(), rng.get_f32(), rng.get_f32()])
+                .collect();
+
+            let outputs = batch.propagate_with_state(&inputs, &mut states);
+
+            for (((network, inputs), outputs), state) in networks
+                .iter()
+                .zip(&inputs)
+                .zip(&outputs)
+                .zip(&mut expected_states)
+            {
+                let expected = network.propagate_with_state(inputs.to_vec(), state);
+
+                assert_relative_eq!(outputs.as_slice(), expected.as_slice(), epsilon = 1e-5);
+            }
+
+            for (state, expected) in states.iter().zip(&expected_states) {
+                assert_relative_eq!(state.layer(1), expected.layer(1), epsilon = 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let batch = NetworkBatch::new(&[]);
+        let inputs: [Vec<f32>; 0] = [];
+
+        assert_eq!(batch.len(), 0);
+        assert!(batch.is_empty());
+        assert!(batch.propagate(&inputs).is_empty());
+
+        let rng = PetriRand::with_seed(Default::default());
+
+        assert!(!NetworkBatch::new(&[Network::random(&rng, topology())]).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Networks must share a topology")]
+    fn mismatched_topologies() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        NetworkBatch::new(&[
+            Network::random(&rng, vec![3, 2]),
+            Network::random(&rng, vec![3, 3]),
+        ]);
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+
+use crate::TopologyError;
+
+/// Why a `Network` could not be built from a topology and its parameters, or loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkError {
+    Topology(TopologyError),
+    /// The number of parameters given doesn't match the topology.
+    ParameterCount {
+        expected: usize,
+        actual: usize,
+    },
+    /// A saved network could not be parsed or decoded.
+    Format(String),
+    /// The bytes don't start with the header of the binary format.
+    NotANetwork,
+    /// The binary format is of a version this build doesn't know about.
+    UnsupportedVersion {
+        version: u16,
+    },
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Topology(error) => write!(f, "invalid topology: {}", error),
+            Self::ParameterCount { expected, actual } => write!(
+                f,
+                "topology has {} parameters, but {} were given",
+                expected, actual
+            ),
+            Self::Format(error) => write!(f, "invalid saved network: {}", error),
+            Self::NotANetwork => write!(f, "not a saved network"),
+            Self::UnsupportedVersion { version } => {
+                write!(f, "unsupported saved network version {}", version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Topology(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<TopologyError> for NetworkError {
+    fn from(error: TopologyError) -> Self {
+        Self::Topology(error)
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(feature = "bevy")]
+use bevy::ecs::component::Component;
+
+use crate::Activation;
+
+/// A network of single neurons wired up as any feed-forward graph, such as the
+/// ones NEAT genomes grow. Node ids `0..inputs` are the inputs and `inputs` is the
+/// bias, which always outputs `1.0`, followed by the outputs, then any hidden nodes.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "bevy", derive(Component))]
+pub struct GraphNetwork {
+    inputs: usize,
+    outputs: usize,
+    /// Applied by every node but the inputs and bias. There are no per-neuron
+    /// coefficients, so `PRelu` acts like `Relu`.
+    activation: Activation,
+    /// The nodes to evaluate, in an order where every node comes after its inputs.
+    order: Vec<usize>,
+    /// The `(from, weight)` of each node's incoming connections.
+    incoming: Vec<Vec<(usize, f32)>>,
+}
+
+impl GraphNetwork {
+    /// Builds the network from `(from, to, weight)` connections. Panics if they
+    /// form a cycle, or lead into an input or the bias.
+    pub fn new(
+        inputs: usize,
+        outputs: usize,
+        activation: Activation,
+        connections: impl IntoIterator<Item = (usize, usize, f32)>,
+    ) -> Self {
+        let mut incoming: Vec<Vec<(usize, f32)>> = vec![Vec::new(); inputs + 1 + outputs];
+
+        for (from, to, weight) in connections {
+            assert!(to > inputs, "Connection into input or bias {}", to);
+
+            let nodes = incoming.len().max(from.max(to) + 1);
+            incoming.resize(nodes, Vec::new());
+            incoming[to].push((from, weight));
+        }
+
+        // Kahn's algorithm, counting how many inputs of each node are still pending
+        let mut pending: Vec<usize> = incoming.iter().map(Vec::len).collect();
+        let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); incoming.len()];
+
+        for (to, connections) in incoming.iter().enumerate() {
+            for &(from, _) in connections {
+                outgoing[from].push(to);
+            }
+        }
+
+        let mut ready: Vec<usize> = (0..incoming.len()).filter(|&n| pending[n] == 0).collect();
+        let mut order = Vec::with_capacity(incoming.len());
+
+        while let Some(node) = ready.pop() {
+            if node > inputs {
+                order.push(node);
+            }
+
+            for &next in &outgoing[node] {
+                pending[next] -= 1;
+
+                if pending[next] == 0 {
+                    ready.push(next);
+                }
+            }
+        }
+
+        assert!(
+            pending.iter().all(|&count| count == 0),
+            "Connections form a cycle"
+        );
+
+        Self {
+            inputs,
+            outputs,
+            activation,
+            order,
+            incoming,
+        }
+    }
+
+    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
+        debug_assert!(inputs.len() == self.inputs);
+
+        let mut values = inputs;
+        values.push(1.0);
+        values.resize(self.incoming.len(), 0.0);
+
+        for &node in &self.order {
+            let sum: f32 = self.incoming[node]
+                .iter()
+                .map(|&(from, weight)| values[from] * weight)
+                .sum();
+
+            values[node] = self.activation.apply(sum, 0.0);
+        }
+
+        values.truncate(self.inputs + 1 + self.outputs);
+        values.split_off(self.inputs + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::*;
+
+    #[test]
+    fn propagate() {
+        // Hidden node 4 sits between the inputs and output 3, which also has a
+        // direct connection from input 0 and the bias
+        let network = GraphNetwork::new(
+            2,
+            1,
+            Activation::Identity,
+            vec![
+                (4, 3, 0.5),
+                (0, 4, 1.0),
+                (1, 4, 2.0),
+                (0, 3, -1.0),
+                (2, 3, 0.25),
+            ],
+        );
+
+        let result = network.propagate(vec![3.0, 4.0]);
+
+        assert_relative_eq!(
+            result.as_slice(),
+            [0.5 * (3.0 + 2.0 * 4.0) - 3.0 + 0.25].as_ref()
+        );
+        assert_relative_eq!(network.propagate(vec![0.0, 0.0])[0], 0.25);
+    }
+
+    #[test]
+    #[should_panic(expected = "Connections form a cycle")]
+    fn cycle() {
+        GraphNetwork::new(
+            1,
+            1,
+            Activation::Identity,
+            vec![(0, 3, 1.0), (3, 4, 1.0), (4, 3, 1.0), (4, 2, 1.0)],
+        );
+    }
+}
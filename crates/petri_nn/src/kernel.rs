@@ -0,0 +1,234 @@
+use crate::Activation;
+
+/// How many partial sums the lane kernels keep apart. Eight `f32`s fill an AVX
+/// register, and split evenly into narrower ones.
+const LANES: usize = 8;
+
+/// Evaluates a dense layer: each neuron's bias plus the dot product of its row of
+/// `weights` with `inputs`, passed through `activation`. Uses the lane kernels,
+/// unless the `scalar` feature asks for the plain ones.
+#[inline]
+pub(crate) fn dense(
+    weights: &[f32],
+    biases: &[f32],
+    coefficients: &[f32],
+    activation: Activation,
+    inputs: &[f32],
+    outputs: &mut [f32],
+) {
+    if cfg!(feature = "scalar") {
+        dense_scalar(weights, biases, coefficients, activation, inputs, outputs)
+    } else {
+        dense_lanes(weights, biases, coefficients, activation, inputs, outputs)
+    }
+}
+
+/// One neuron at a time, summing its weighted inputs in order.
+fn dense_scalar(
+    weights: &[f32],
+    biases: &[f32],
+    coefficients: &[f32],
+    activation: Activation,
+    inputs: &[f32],
+    outputs: &mut [f32],
+) {
+    for (((output, row), bias), &coefficient) in outputs
+        .iter_mut()
+        .zip(weights.chunks_exact(inputs.len()))
+        .zip(biases)
+        .zip(coefficients)
+    {
+        let sum = bias
+            + inputs
+                .iter()
+                .zip(row)
+                .map(|(input, weight)| input * weight)
+                .sum::<f32>();
+
+        *output = activation.apply(sum, coefficient);
+    }
+}
+
+/// Sums each row in `LANES` independent accumulators, then activates the whole layer
+/// in one pass, so both loops can be auto-vectorised. Sums are reassociated, so
+/// results can differ from `dense_scalar` by rounding.
+fn dense_lanes(
+    weights: &[f32],
+    biases: &[f32],
+    coefficients: &[f32],
+    activation: Activation,
+    inputs: &[f32],
+    outputs: &mut [f32],
+) {
+    for ((output, row), bias) in outputs
+        .iter_mut()
+        .zip(weights.chunks_exact(inputs.len()))
+        .zip(biases)
+    {
+        *output = bias + dot_lanes(row, inputs);
+    }
+
+    activate_lanes(activation, outputs, coefficients);
+}
+
+/// Sums a dense layer for a batch of networks stored as structure of arrays, with
+/// `weights` indexed by neuron, then input, then network, and the other slices by
+/// neuron or input, then network. The innermost loop runs across the networks, so
+/// it vectorises whatever the shape of the layer. Doesn't activate the sums.
+pub(crate) fn dense_batch(
+    weights: &[f32],
+    biases: &[f32],
+    inputs: &[f32],
+    outputs: &mut [f32],
+    networks: usize,
+) {
+    // Each neuron has a weight for every input of every network
+    let row = inputs.len();
+
+    for ((outputs, biases), weights) in outputs
+        .chunks_exact_mut(networks)
+        .zip(biases.chunks_exact(networks))
+        .zip(weights.chunks_exact(row))
+    {
+        outputs.copy_from_slice(biases);
+
+        for (inputs, weights) in inputs
+            .chunks_exact(networks)
+            .zip(weights.chunks_exact(networks))
+        {
+            for ((output, input), weight) in outputs.iter_mut().zip(inputs).zip(weights) {
+                *output += input * weight;
+            }
+        }
+    }
+}
+
+/// Applies the activation to every value, with the lane kernel unless the `scalar`
+/// feature asks for the plain one.
+#[inline]
+pub(crate) fn activate(activation: Activation, values: &mut [f32], coefficients: &[f32]) {
+    if cfg!(feature = "scalar") {
+        for (value, &coefficient) in values.iter_mut().zip(coefficients) {
+            *value = activation.apply(*value, coefficient);
+        }
+    } else {
+        activate_lanes(activation, values, coefficients)
+    }
+}
+
+fn dot_lanes(a: &[f32], b: &[f32]) -> f32 {
+    debug_assert!(a.len() == b.len());
+
+    let body = a.len() - a.len() % LANES;
+    let (a, a_tail) = a.split_at(body);
+    let (b, b_tail) = b.split_at(body);
+
+    let mut sums = [0.0; LANES];
+
+    for (a, b) in a.chunks_exact(LANES).zip(b.chunks_exact(LANES)) {
+        let a: &[f32; LANES] = a.try_into().unwrap();
+        let b: &[f32; LANES] = b.try_into().unwrap();
+
+        for ((sum, a), b) in sums.iter_mut().zip(a).zip(b) {
+            *sum += a * b;
+        }
+    }
+
+    let tail = a_tail.iter().zip(b_tail).map(|(a, b)| a * b).sum::<f32>();
+
+    sums.iter().sum::<f32>() + tail
+}
+
+/// Applies the activation to every value, with the `match` hoisted out of the loop.
+fn activate_lanes(activation: Activation, values: &mut [f32], coefficients: &[f32]) {
+    fn each(values: &mut [f32], f: impl Fn(f32) -> f32) {
+        values.iter_mut().for_each(|value| *value = f(*value));
+    }
+
+    match activation {
+        Activation::PRelu => {
+            for (value, &coefficient) in values.iter_mut().zip(coefficients) {
+                *value = Activation::PRelu.apply(*value, coefficient);
+            }
+        }
+        Activation::Sigmoid => each(values, |x| Activation::Sigmoid.apply(x, 0.0)),
+        Activation::Tanh => each(values, |x| Activation::Tanh.apply(x, 0.0)),
+        Activation::Relu => each(values, |x| Activation::Relu.apply(x, 0.0)),
+        Activation::LeakyRelu => each(values, |x| Activation::LeakyRelu.apply(x, 0.0)),
+        Activation::Softsign => each(values, |x| Activation::Softsign.apply(x, 0.0)),
+        Activation::Gaussian => each(values, |x| Activation::Gaussian.apply(x, 0.0)),
+        Activation::Sine => each(values, |x| Activation::Sine.apply(x, 0.0)),
+        Activation::Identity => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::*;
+    use petri_rand::*;
+    use std::iter::repeat_with;
+
+    fn values(rng: &PetriRand, len: usize) -> Vec<f32> {
+        repeat_with(|| rng.range_f32(-1.0..1.0)).take(len).collect()
+    }
+
+    #[test]
+    fn dot_lanes() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        // Shorter than, a multiple of, and with a tail past the lanes
+        for len in [0, 1, 5, 8, 16, 21, 100] {
+            let (a, b) = (values(&rng, len), values(&rng, len));
+            let expected = a.iter().zip(&b).map(|(a, b)| a * b).sum::<f32>();
+
+            assert_relative_eq!(
+                super::dot_lanes(&a, &b),
+                expected,
+                epsilon = 1e-5,
+                max_relative = 1e-5
+            );
+        }
+    }
+
+    #[test]
+    fn dense_paths_agree() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        for activation in Activation::ALL {
+            for (inputs, size) in [(3, 2), (8, 5), (11, 22), (37, 13)] {
+                let weights = values(&rng, inputs * size);
+                let biases = values(&rng, size);
+                let coefficients = values(&rng, size);
+                let inputs = values(&rng, inputs);
+
+                let mut scalar = vec![0.0; size];
+                let mut lanes = vec![0.0; size];
+
+                dense_scalar(
+                    &weights,
+                    &biases,
+                    &coefficients,
+                    activation,
+                    &inputs,
+                    &mut scalar,
+                );
+                dense_lanes(
+                    &weights,
+                    &biases,
+                    &coefficients,
+                    activation,
+                    &inputs,
+                    &mut lanes,
+                );
+
+                assert_relative_eq!(
+                    scalar.as_slice(),
+                    lanes.as_slice(),
+                    epsilon = 1e-5,
+                    max_relative = 1e-5
+                );
+            }
+        }
+    }
+}
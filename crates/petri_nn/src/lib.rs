@@ -1,206 +1,409 @@
-#![feature(array_windows)]
-
+#[cfg(feature = "bevy")]
 use bevy::ecs::component::Component;
 use petri_rand::*;
-use std::iter::{once, repeat, repeat_with};
-
-#[derive(Component, Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+pub use crate::{
+    activation::Activation,
+    batch::NetworkBatch,
+    error::NetworkError,
+    graph::GraphNetwork,
+    save::{FORMAT_VERSION, MAGIC},
+    scratch::Scratch,
+    state::HiddenState,
+    topology::{Layer, LayerKind, Source, Topology, TopologyError},
+    train::{mean_squared_error, Optimizer, Trainer},
+    view::{LayerView, NeuronView},
+};
+
+use crate::parameters::LayerParameters;
+
+mod activation;
+mod batch;
+mod error;
+mod graph;
+mod kernel;
+mod parameters;
+mod save;
+mod scratch;
+mod state;
+mod topology;
+mod train;
+mod view;
+
+/// Serializes as its topology and `weights()`, so it can be saved in any serde format,
+/// besides the ones of `to_ron` and `to_bytes`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bevy", derive(Component))]
+#[serde(into = "save::SavedNetwork", try_from = "save::SavedNetwork")]
 pub struct Network {
-    layers: Vec<usize>,
-    neurons: Vec<Neuron>,
+    topology: Topology,
+    layers: Vec<LayerParameters>,
+    layout: Layout,
 }
 
+/// Where everything goes in the flat buffers used while propagating, worked out
+/// once from the topology.
 #[derive(Debug, Clone)]
-struct Neuron {
-    bias: f32,
-    coefficient: f32,
-    weights: Vec<f32>,
+struct Layout {
+    /// Where each input head starts in the concatenated inputs.
+    inputs: Vec<usize>,
+    /// Where each layer's outputs start in a `Scratch`, followed by their total length.
+    layers: Vec<usize>,
+    /// The longest input of the layers that have theirs gathered.
+    gathered: usize,
 }
 
 impl Network {
-    pub fn random(rng: &PetriRand, layers: Vec<usize>) -> Self {
-        debug_assert!(layers.len() > 1);
+    pub fn random(rng: &PetriRand, topology: impl Into<Topology>) -> Self {
+        let topology = topology.into();
+
+        assert_valid(&topology);
 
-        let neurons = layers
-            .array_windows()
-            .flat_map(|&[input, output]| repeat(input).take(output))
-            .map(|input| Neuron::random(rng, input))
+        let layers = topology
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| {
+                LayerParameters::random(rng, layer, topology.neuron_inputs(index))
+            })
             .collect();
 
-        Self { neurons, layers }
+        Self::new(topology, layers)
     }
 
-    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
-        let (result, _) =
-            self.layers
-                .array_windows()
-                .fold((inputs, 0), |(inputs, from), [_, output]| {
-                    let to = from + output;
-
-                    (
-                        self.neurons[from..to]
-                            .iter()
-                            .map(|neuron| neuron.propagate(&inputs))
-                            .collect(),
-                        to,
-                    )
-                });
-
-        result
+    fn new(topology: Topology, layers: Vec<LayerParameters>) -> Self {
+        Self {
+            layout: Layout::new(&topology),
+            topology,
+            layers,
+        }
     }
 
-    pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
-        self.neurons
-            .iter()
-            .flat_map(|neuron| neuron.weights())
-            .copied()
+    pub fn topology(&self) -> &Topology {
+        &self.topology
     }
 
-    pub fn adjust_weights(&mut self, weights: impl IntoIterator<Item = f32>) {
-        let mut weights = weights.into_iter();
+    /// Replaces the activation of every layer.
+    pub fn with_activations(mut self, activations: Vec<Activation>) -> Self {
+        self.set_activations(activations);
+        self
+    }
 
-        self.neurons = self
-            .layers
-            .array_windows()
-            .flat_map(|&[input, output]| repeat(input).take(output))
-            .map(|input| Neuron::from_weights(input, &mut weights))
-            .collect();
+    pub fn activations(&self) -> impl ExactSizeIterator<Item = Activation> + '_ {
+        self.topology.layers.iter().map(|layer| layer.activation)
+    }
+
+    pub fn set_activations(&mut self, activations: Vec<Activation>) {
+        assert!(
+            activations.len() == self.topology.layers.len(),
+            "Expected one activation per layer"
+        );
 
-        if weights.next().is_some() {
-            panic!("Too many weights given");
+        for (layer, activation) in self.topology.layers.iter_mut().zip(activations) {
+            layer.activation = activation;
         }
     }
 
-    pub fn from_weights(layers: Vec<usize>, weights: impl IntoIterator<Item = f32>) -> Self {
-        debug_assert!(layers.len() > 1);
+    /// Propagates the concatenated input heads, returning the concatenated output heads.
+    /// Recurrent layers start from a zeroed hidden state every time.
+    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
+        self.propagate_into(&inputs, &mut self.scratch()).to_vec()
+    }
 
-        let mut weights = weights.into_iter();
+    /// Like `propagate`, but recurrent layers carry their outputs over to the next
+    /// call through `state`.
+    pub fn propagate_with_state(&self, inputs: Vec<f32>, state: &mut HiddenState) -> Vec<f32> {
+        self.propagate_into_with_state(&inputs, &mut self.scratch(), state)
+            .to_vec()
+    }
 
-        let neurons = layers
-            .array_windows()
-            .flat_map(|&[input, output]| repeat(input).take(output))
-            .map(|input| Neuron::from_weights(input, &mut weights))
-            .collect();
+    /// Like `propagate`, but works within the buffers of `scratch` and returns the
+    /// outputs from there, so it doesn't allocate once `scratch` fits the network.
+    pub fn propagate_into<'a>(&self, inputs: &[f32], scratch: &'a mut Scratch) -> &'a [f32] {
+        self.evaluate(inputs, scratch, None);
+        self.outputs(scratch)
+    }
 
-        if weights.next().is_some() {
-            panic!("Too many weights given");
-        }
+    /// Like `propagate_with_state`, but without allocating as with `propagate_into`.
+    pub fn propagate_into_with_state<'a>(
+        &self,
+        inputs: &[f32],
+        scratch: &'a mut Scratch,
+        state: &mut HiddenState,
+    ) -> &'a [f32] {
+        self.evaluate(inputs, scratch, Some(state));
+        self.outputs(scratch)
+    }
+
+    /// Propagates each input head separately, returning each output head.
+    pub fn propagate_heads(&self, inputs: &[&[f32]]) -> Vec<Vec<f32>> {
+        debug_assert!(inputs.len() == self.topology.inputs.len());
 
-        Self { layers, neurons }
+        let mut scratch = self.scratch();
+        self.evaluate(&inputs.concat(), &mut scratch, None);
+
+        self.topology
+            .outputs
+            .iter()
+            .map(|&output| self.layer_outputs(&scratch, output).to_vec())
+            .collect()
+    }
+
+    /// A zeroed hidden state for the network's recurrent layers.
+    pub fn hidden_state(&self) -> HiddenState {
+        HiddenState::new(&self.topology)
     }
-}
 
-impl Neuron {
-    pub fn random(rng: &PetriRand, input_size: usize) -> Self {
-        let bias = rng.get_f32_normalised();
+    /// Buffers that fit the network, for `propagate_into`.
+    pub fn scratch(&self) -> Scratch {
+        let mut scratch = Scratch::new();
+        self.fit(&mut scratch);
+        scratch
+    }
 
-        let coefficient = rng.get_f32_normalised();
+    fn fit(&self, scratch: &mut Scratch) {
+        scratch.fit(
+            self.layout.layers[self.layers.len()],
+            self.layout.gathered,
+            self.topology.output_size(),
+        );
+    }
 
-        let weights = repeat_with(|| rng.get_f32_normalised())
-            .take(input_size)
-            .collect();
+    /// Writes the outputs of every layer into `scratch`.
+    fn evaluate(&self, inputs: &[f32], scratch: &mut Scratch, mut state: Option<&mut HiddenState>) {
+        debug_assert!(inputs.len() == self.topology.input_size());
+        if let Some(state) = &state {
+            debug_assert!(state.fits(&self.topology));
+        }
 
-        Self {
-            bias,
-            coefficient,
-            weights,
+        self.fit(scratch);
+
+        let topology = &self.topology;
+        let layout = &self.layout;
+
+        for (index, (layer, parameters)) in topology.layers.iter().zip(&self.layers).enumerate() {
+            // Sources always come before the layer, so are in the finished part
+            let (finished, rest) = scratch.values.split_at_mut(layout.layers[index]);
+            let outputs = &mut rest[..layer.size];
+            let finished = &*finished;
+
+            let source = |source: &Source| match *source {
+                Source::Input(input) => &inputs[layout.inputs[input]..][..topology.inputs[input]],
+                Source::Layer(earlier) => {
+                    &finished[layout.layers[earlier]..][..topology.layers[earlier].size]
+                }
+            };
+
+            // Only gather the inputs when they come from more than one place
+            let layer_inputs = match (layer.sources.as_slice(), layer.kind) {
+                ([single], LayerKind::Dense) => source(single),
+                (sources, kind) => {
+                    let mut len = 0;
+
+                    for values in sources.iter().map(source) {
+                        scratch.gathered[len..][..values.len()].copy_from_slice(values);
+                        len += values.len();
+                    }
+
+                    if kind == LayerKind::Elman {
+                        let recurrent = &mut scratch.gathered[len..][..layer.size];
+
+                        match state.as_deref() {
+                            Some(state) => recurrent.copy_from_slice(state.layer(index)),
+                            None => recurrent.fill(0.0),
+                        }
+
+                        len += layer.size;
+                    }
+
+                    &scratch.gathered[..len]
+                }
+            };
+
+            parameters.propagate(layer_inputs, layer.activation, outputs);
+
+            if let (LayerKind::Elman, Some(state)) = (layer.kind, state.as_deref_mut()) {
+                state.layer_mut(index).copy_from_slice(outputs);
+            }
         }
     }
 
-    pub fn propagate(&self, inputs: &[f32]) -> f32 {
-        debug_assert!(inputs.len() == self.weights.len());
+    /// The output heads of an evaluated `scratch`, one after another.
+    fn outputs<'a>(&self, scratch: &'a mut Scratch) -> &'a [f32] {
+        match self.topology.outputs.as_slice() {
+            &[output] => self.layer_outputs(scratch, output),
+            outputs => {
+                let mut len = 0;
+
+                for &output in outputs {
+                    let (from, size) = (self.layout.layers[output], self.layers[output].size());
 
-        let output = self.bias
-            + inputs
-                .iter()
-                .zip(&self.weights)
-                .map(|(input, weight)| input * weight)
-                .sum::<f32>();
+                    scratch.outputs[len..][..size].copy_from_slice(&scratch.values[from..][..size]);
+                    len += size;
+                }
 
-        if output > 0.0 {
-            output
-        } else {
-            self.coefficient * output
+                &scratch.outputs[..len]
+            }
         }
     }
 
-    pub fn weights(&self) -> impl Iterator<Item = &f32> + '_ {
-        once(&self.bias)
-            .chain(once(&self.coefficient))
-            .chain(self.weights.iter())
+    fn layer_outputs<'a>(&self, scratch: &'a Scratch, layer: usize) -> &'a [f32] {
+        &scratch.values[self.layout.layers[layer]..][..self.layers[layer].size()]
     }
 
-    pub fn from_weights(input_neurons: usize, weights: &mut impl Iterator<Item = f32>) -> Self {
-        let bias = weights.next().unwrap();
-        let coefficient = weights.next().unwrap();
+    /// The number of parameters in `weights()`.
+    pub fn parameter_count(&self) -> usize {
+        self.topology.parameter_count()
+    }
 
-        let weights = repeat_with(|| weights.next().unwrap())
-            .take(input_neurons)
-            .collect();
+    /// Every layer with its parameters, in order.
+    pub fn layers(&self) -> impl ExactSizeIterator<Item = LayerView<'_>> + '_ {
+        let mut offset = 0;
 
-        Self {
-            bias,
-            weights,
-            coefficient,
-        }
+        self.topology
+            .layers
+            .iter()
+            .zip(&self.layers)
+            .enumerate()
+            .map(move |(index, (layer, parameters))| {
+                let count = self.topology.layer_parameter_count(index);
+                let view = LayerView::new(index, layer, parameters, offset, count);
+
+                offset += count;
+                view
+            })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::*;
-    use approx::*;
+    /// Every parameter of the network: for each neuron its bias, coefficient, then
+    /// the weights of the connections it has.
+    pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
+        self.topology
+            .layers
+            .iter()
+            .zip(&self.layers)
+            .flat_map(|(layer, parameters)| parameters.parameters(layer))
+    }
 
-    mod neuron {
-        use super::*;
+    /// The activation of each layer encoded as genes, kept apart from `weights()` so
+    /// that the weight layout doesn't depend on whether activations are evolved.
+    pub fn activation_genes(&self) -> impl Iterator<Item = f32> + '_ {
+        self.activations().map(|activation| activation.to_gene())
+    }
 
-        #[test]
-        fn random() {
-            // Because we always use the same seed, our `rng` in here will
-            // always return the same set of values
-            let rng = PetriRand::with_seed(Default::default());
-            let neuron = Neuron::random(&rng, 4);
+    pub fn adjust_activations(&mut self, genes: impl IntoIterator<Item = f32>) {
+        self.set_activations(genes.into_iter().map(Activation::from_gene).collect());
+    }
 
-            assert_relative_eq!(neuron.bias, 0.7654091);
-            assert_relative_eq!(neuron.coefficient, -0.77652967);
-            assert_relative_eq!(
-                neuron.weights.as_slice(),
-                [-0.63342345, 0.9910984, -0.90073603, 0.9265579].as_ref()
-            );
+    /// Replaces every parameter, laid out as in `weights()`. Panics when there are too
+    /// many or too few.
+    pub fn adjust_weights(&mut self, weights: impl IntoIterator<Item = f32>) {
+        if let Err(error) = self.try_adjust_weights(weights) {
+            panic!("{}", error);
         }
+    }
 
-        #[test]
-        fn propagate() {
-            let neuron = Neuron {
-                bias: 0.5,
-                coefficient: 0.1,
-                weights: vec![-0.3, 0.8],
-            };
+    /// Like `adjust_weights`, but returns an error instead of panicking, leaving the
+    /// network unchanged.
+    pub fn try_adjust_weights(
+        &mut self,
+        weights: impl IntoIterator<Item = f32>,
+    ) -> Result<(), NetworkError> {
+        self.layers = layers_from_weights(&self.topology, weights)?;
+        Ok(())
+    }
 
-            // Ensures our PReLU works for < 0.0 cases:
-            assert_relative_eq!(
-                neuron.propagate(&[-10.0, -10.0]),
-                ((-0.3 * -10.0) + (0.8 * -10.0) + 0.5) * 0.1
-            );
+    /// Builds a network from parameters laid out as in `weights()`. Panics when the
+    /// topology is invalid, or there are too many or too few parameters for it.
+    pub fn from_weights(
+        topology: impl Into<Topology>,
+        weights: impl IntoIterator<Item = f32>,
+    ) -> Self {
+        Self::try_from_weights(topology, weights).unwrap_or_else(|error| panic!("{}", error))
+    }
 
-            // `0.5` and `1.0` chosen by a fair dice roll:
-            assert_relative_eq!(
-                neuron.propagate(&[0.5, 1.0]),
-                (-0.3 * 0.5) + (0.8 * 1.0) + 0.5,
-            );
-        }
+    /// Like `from_weights`, but returns an error instead of panicking.
+    pub fn try_from_weights(
+        topology: impl Into<Topology>,
+        weights: impl IntoIterator<Item = f32>,
+    ) -> Result<Self, NetworkError> {
+        let topology = topology.into();
 
-        #[test]
-        fn from_weights() {
-            let weights: Vec<f32> = vec![0.5, 0.1, 0.9, 0.8, -0.1];
+        topology.validate()?;
+
+        let layers = layers_from_weights(&topology, weights)?;
 
-            let neuron = Neuron::from_weights(3, &mut weights.into_iter());
+        Ok(Self::new(topology, layers))
+    }
+}
 
-            assert_relative_eq!(neuron.bias, 0.5);
-            assert_relative_eq!(neuron.coefficient, 0.1);
-            assert_relative_eq!(neuron.weights.as_slice(), [0.9, 0.8, -0.1].as_ref());
+impl Layout {
+    fn new(topology: &Topology) -> Self {
+        let offsets = |sizes: &mut dyn Iterator<Item = usize>| -> Vec<usize> {
+            let mut offset = 0;
+            let mut offsets: Vec<usize> = sizes
+                .map(|size| {
+                    offset += size;
+                    offset - size
+                })
+                .collect();
+            offsets.push(offset);
+            offsets
+        };
+
+        let gathered = topology
+            .layers
+            .iter()
+            .enumerate()
+            .filter(|(_, layer)| layer.sources.len() > 1 || layer.kind == LayerKind::Elman)
+            .map(|(index, _)| topology.neuron_inputs(index))
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            inputs: offsets(&mut topology.inputs.iter().copied()),
+            layers: offsets(&mut topology.layers.iter().map(|layer| layer.size)),
+            gathered,
         }
     }
+}
+
+fn assert_valid(topology: &Topology) {
+    if let Err(error) = topology.validate() {
+        panic!("{}", NetworkError::from(error));
+    }
+}
+
+fn layers_from_weights(
+    topology: &Topology,
+    weights: impl IntoIterator<Item = f32>,
+) -> Result<Vec<LayerParameters>, NetworkError> {
+    let weights: Vec<f32> = weights.into_iter().collect();
+    let expected = topology.parameter_count();
+
+    if weights.len() != expected {
+        return Err(NetworkError::ParameterCount {
+            expected,
+            actual: weights.len(),
+        });
+    }
+
+    let mut weights = weights.into_iter();
+
+    Ok(topology
+        .layers
+        .iter()
+        .enumerate()
+        .map(|(index, layer)| {
+            LayerParameters::from_weights(layer, topology.neuron_inputs(index), &mut weights)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use approx::*;
 
     mod network {
         use super::*;
@@ -212,8 +415,8 @@ mod tests {
 
             let network = Network::random(&rng, topology);
 
-            assert_eq!(network.layers.len(), 2);
-            assert_eq!(network.neurons[0].weights.len(), 4);
+            assert_eq!(network.topology, Topology::dense(&[4, 2]));
+            assert_eq!(network.layers[0].row(0).len(), 4);
         }
 
         #[test]
@@ -221,26 +424,12 @@ mod tests {
             let inputs = vec![0.5, 1.0, 0.75];
             let layers = vec![3, 2, 1];
 
-            let network = Network {
-                layers,
-                neurons: vec![
-                    Neuron {
-                        bias: 0.5,
-                        coefficient: 0.1,
-                        weights: vec![-0.3, 0.8, 0.1],
-                    },
-                    Neuron {
-                        bias: 0.5,
-                        coefficient: 0.1,
-                        weights: vec![-0.3, -0.8, -0.1],
-                    },
-                    Neuron {
-                        bias: 0.5,
-                        coefficient: 0.1,
-                        weights: vec![0.4, -0.2],
-                    },
-                ],
-            };
+            #[rustfmt::skip]
+            let network = Network::from_weights(layers, vec![
+                0.5, 0.1, -0.3, 0.8, 0.1,
+                0.5, 0.1, -0.3, -0.8, -0.1,
+                0.5, 0.1, 0.4, -0.2,
+            ]);
 
             let result = network.propagate(inputs);
 
@@ -257,14 +446,7 @@ mod tests {
         #[test]
         fn weights() {
             let layers = vec![2, 1];
-            let network = Network {
-                layers,
-                neurons: vec![Neuron {
-                    bias: 0.5,
-                    coefficient: 0.1,
-                    weights: vec![-0.3, 0.8],
-                }],
-            };
+            let network = Network::from_weights(layers, vec![0.5, 0.1, -0.3, 0.8]);
 
             let mut network_weights = network.weights();
 
@@ -282,13 +464,184 @@ mod tests {
 
             let network = Network::from_weights(topology, weights);
 
-            assert_eq!(network.neurons.len(), 1);
-            assert_relative_eq!(network.neurons[0].bias, 0.5);
-            assert_relative_eq!(network.neurons[0].coefficient, 0.1);
-            assert_relative_eq!(
-                network.neurons[0].weights.as_slice(),
-                &[0.9, -0.3, 0.2, -0.1].as_ref()
+            assert_eq!(network.layers.len(), 1);
+            assert_eq!(network.layers[0].size(), 1);
+            assert_relative_eq!(network.layers[0].row(0), [0.9, -0.3, 0.2, -0.1].as_ref());
+            assert!(network.weights().eq([0.5, 0.1, 0.9, -0.3, 0.2, -0.1]));
+        }
+
+        #[test]
+        fn try_from_weights() {
+            let weights = vec![0.5, 0.1, 0.9, -0.3, 0.2, -0.1];
+
+            assert!(Network::try_from_weights(vec![4, 1], weights.clone()).is_ok());
+            assert_eq!(
+                Network::try_from_weights(vec![4, 1], weights[1..].to_vec()).unwrap_err(),
+                NetworkError::ParameterCount {
+                    expected: 6,
+                    actual: 5
+                }
+            );
+            assert_eq!(
+                Network::try_from_weights(vec![4, 1], weights.iter().chain(&[0.0]).copied())
+                    .unwrap_err(),
+                NetworkError::ParameterCount {
+                    expected: 6,
+                    actual: 7
+                }
+            );
+            assert_eq!(
+                Network::try_from_weights(vec![4], weights).unwrap_err(),
+                NetworkError::Topology(TopologyError::NoLayers)
+            );
+            assert_eq!(
+                Network::try_from_weights(Vec::<usize>::new(), Vec::new()).unwrap_err(),
+                NetworkError::Topology(TopologyError::NoInputs)
+            );
+        }
+
+        #[test]
+        fn try_adjust_weights() {
+            let mut network = Network::from_weights(vec![2, 1], vec![0.5, 0.1, -0.3, 0.8]);
+
+            assert!(network.try_adjust_weights(vec![0.0; 3]).is_err());
+            assert!(network.weights().eq([0.5, 0.1, -0.3, 0.8]));
+
+            assert!(network.try_adjust_weights(vec![0.0; 4]).is_ok());
+            assert!(network.weights().eq([0.0; 4]));
+        }
+
+        #[test]
+        #[should_panic(expected = "topology has 6 parameters, but 5 were given")]
+        fn from_weights_too_few() {
+            Network::from_weights(vec![4, 1], vec![0.5, 0.1, 0.9, -0.3, 0.2]);
+        }
+
+        #[test]
+        fn propagate_with_activations() {
+            let network =
+                Network::from_weights(vec![2, 1, 1], vec![0.5, 0.1, -0.3, 0.8, 0.0, 0.1, 2.0])
+                    .with_activations(vec![Activation::Tanh, Activation::Sigmoid]);
+
+            let hidden = ((-0.3 * 0.2) + (0.8 * 0.4) + 0.5f32).tanh();
+            let expected = 1.0 / (1.0 + (-2.0 * hidden).exp());
+
+            assert_relative_eq!(network.propagate(vec![0.2, 0.4])[0], expected);
+        }
+
+        #[test]
+        fn activation_genes() {
+            let rng = PetriRand::with_seed(Default::default());
+            let mut network = Network::random(&rng, vec![3, 2, 2, 1]);
+
+            assert!(network
+                .activations()
+                .all(|activation| activation == Activation::PRelu));
+
+            let other = Network::random(&rng, vec![3, 2, 2, 1]).with_activations(vec![
+                Activation::Gaussian,
+                Activation::Relu,
+                Activation::Sine,
+            ]);
+
+            network.adjust_activations(other.activation_genes());
+
+            assert!(network.activations().eq(other.activations()));
+        }
+
+        #[test]
+        fn propagate_heads() {
+            // A skip connection from the first input head straight to the output,
+            // and a second output head reading the hidden layer
+            let topology = Topology {
+                inputs: vec![2, 1],
+                layers: vec![
+                    Layer::new(1, vec![Source::Input(1)]).with_activation(Activation::Identity),
+                    Layer::new(1, vec![Source::Layer(0), Source::Input(0)])
+                        .with_activation(Activation::Identity)
+                        .with_mask(vec![vec![true, false, true]]),
+                ],
+                outputs: vec![1, 0],
+            };
+
+            #[rustfmt::skip]
+            let weights = vec![
+                0.5, 0.1, 2.0,
+                0.0, 0.1, 3.0, 4.0,
+            ];
+
+            let network = Network::from_weights(topology, weights.clone());
+
+            let hidden = 0.5 + 2.0 * 10.0;
+            let output = 3.0 * hidden + 4.0 * 2.0;
+
+            assert_eq!(
+                network.propagate_heads(&[&[1.0, 2.0], &[10.0]]),
+                vec![vec![output], vec![hidden]]
+            );
+            assert_eq!(
+                network.propagate(vec![1.0, 2.0, 10.0]),
+                vec![output, hidden]
             );
+
+            // The masked out connection has no weight
+            assert_eq!(network.weights().collect::<Vec<_>>(), weights);
+        }
+
+        #[test]
+        fn propagate_into() {
+            let rng = PetriRand::with_seed(Default::default());
+
+            let mut topology = Topology::dense(&[3, 4, 4, 2]);
+            topology.layers[1].kind = LayerKind::Elman;
+            topology.layers[2].sources.push(Source::Input(0));
+            topology.outputs = vec![2, 0];
+
+            let network = Network::random(&rng, topology);
+            let mut scratch = Scratch::new();
+            let mut state = network.hidden_state();
+            let mut expected_state = network.hidden_state();
+
+            for inputs in [[0.5, 1.0, -0.25], [0.0, 0.1, 0.2], [-1.0, 0.3, 0.9]] {
+                let expected = network.propagate(inputs.to_vec());
+                assert_eq!(network.propagate_into(&inputs, &mut scratch), expected);
+
+                let expected = network.propagate_with_state(inputs.to_vec(), &mut expected_state);
+                assert_eq!(
+                    network.propagate_into_with_state(&inputs, &mut scratch, &mut state),
+                    expected
+                );
+                assert_eq!(state, expected_state);
+            }
+        }
+
+        #[test]
+        fn propagate_with_state() {
+            let topology = Topology {
+                inputs: vec![1],
+                layers: vec![Layer::new(1, vec![Source::Input(0)])
+                    .with_kind(LayerKind::Elman)
+                    .with_activation(Activation::Identity)],
+                outputs: vec![0],
+            };
+
+            // The recurrent weight comes after the input weight
+            let network = Network::from_weights(topology, vec![0.0, 0.1, 1.0, 0.5]);
+            let mut state = network.hidden_state();
+
+            assert_eq!(network.weights().count(), 4);
+
+            assert_relative_eq!(network.propagate_with_state(vec![2.0], &mut state)[0], 2.0);
+            assert_relative_eq!(network.propagate_with_state(vec![1.0], &mut state)[0], 2.0);
+            assert_relative_eq!(network.propagate_with_state(vec![0.0], &mut state)[0], 1.0);
+
+            // Stateless propagation always starts from scratch
+            assert_relative_eq!(network.propagate(vec![1.0])[0], 1.0);
+
+            state.reset();
+
+            assert_eq!(state, network.hidden_state());
+            assert_relative_eq!(network.propagate_with_state(vec![1.0], &mut state)[0], 1.0);
         }
     }
 }
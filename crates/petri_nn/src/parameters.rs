@@ -0,0 +1,268 @@
+use petri_rand::*;
+use std::iter::{once, repeat};
+
+use crate::{kernel, Activation, Layer};
+
+/// The parameters of every neuron in a layer, stored contiguously so the layer can
+/// be propagated as a single matrix-vector product.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LayerParameters {
+    /// The number of weights of each neuron, which is the length of its row.
+    inputs: usize,
+    biases: Vec<f32>,
+    coefficients: Vec<f32>,
+    /// One row of `inputs` weights per neuron. Masked out connections are zero.
+    weights: Vec<f32>,
+}
+
+impl LayerParameters {
+    pub fn random(rng: &PetriRand, layer: &Layer, inputs: usize) -> Self {
+        let mut params = [0.0; 2];
+        let mut biases = Vec::with_capacity(layer.size);
+        let mut coefficients = Vec::with_capacity(layer.size);
+        let mut weights = vec![0.0; layer.size * inputs];
+
+        // Each neuron draws its bias and coefficient before its weights
+        for row in weights.chunks_exact_mut(inputs) {
+            rng.fill_f32_range(&mut params, -1.0..1.0);
+            rng.fill_f32_range(row, -1.0..1.0);
+
+            let [bias, coefficient] = params;
+            biases.push(bias);
+            coefficients.push(coefficient);
+        }
+
+        let mut parameters = Self {
+            inputs,
+            biases,
+            coefficients,
+            weights,
+        };
+
+        parameters.disconnect(layer);
+        parameters
+    }
+
+    pub fn from_weights(
+        layer: &Layer,
+        inputs: usize,
+        weights: &mut impl Iterator<Item = f32>,
+    ) -> Self {
+        let mut biases = Vec::with_capacity(layer.size);
+        let mut coefficients = Vec::with_capacity(layer.size);
+        let mut row_weights = Vec::with_capacity(layer.size * inputs);
+
+        for neuron in 0..layer.size {
+            biases.push(weights.next().unwrap());
+            coefficients.push(weights.next().unwrap());
+
+            row_weights.extend(connected(layer, neuron).take(inputs).map(|connected| {
+                if connected {
+                    weights.next().unwrap()
+                } else {
+                    0.0
+                }
+            }));
+        }
+
+        Self {
+            inputs,
+            biases,
+            coefficients,
+            weights: row_weights,
+        }
+    }
+
+    /// The same shape of layer, with every parameter zero.
+    pub fn zeroed(&self) -> Self {
+        Self {
+            inputs: self.inputs,
+            biases: vec![0.0; self.biases.len()],
+            coefficients: vec![0.0; self.coefficients.len()],
+            weights: vec![0.0; self.weights.len()],
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.biases.len()
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn same_shape(&self, other: &Self) -> bool {
+        self.inputs == other.inputs && self.size() == other.size()
+    }
+
+    pub fn biases(&self) -> &[f32] {
+        &self.biases
+    }
+
+    pub fn coefficients(&self) -> &[f32] {
+        &self.coefficients
+    }
+
+    /// The weights of a neuron, including those of masked out connections.
+    pub fn row(&self, neuron: usize) -> &[f32] {
+        &self.weights[neuron * self.inputs..][..self.inputs]
+    }
+
+    pub fn biases_mut(&mut self) -> &mut [f32] {
+        &mut self.biases
+    }
+
+    pub fn coefficients_mut(&mut self) -> &mut [f32] {
+        &mut self.coefficients
+    }
+
+    pub fn row_mut(&mut self, neuron: usize) -> &mut [f32] {
+        &mut self.weights[neuron * self.inputs..][..self.inputs]
+    }
+
+    /// Every parameter, in no particular order but the same for any layer of the same
+    /// shape, including the weights of masked out connections.
+    pub fn values(&self) -> impl Iterator<Item = &f32> {
+        self.biases
+            .iter()
+            .chain(&self.coefficients)
+            .chain(&self.weights)
+    }
+
+    /// Mutable `values()`, in the same order.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut f32> {
+        self.biases
+            .iter_mut()
+            .chain(&mut self.coefficients)
+            .chain(&mut self.weights)
+    }
+
+    /// Writes the activated output of every neuron for the given inputs.
+    pub fn propagate(&self, inputs: &[f32], activation: Activation, outputs: &mut [f32]) {
+        debug_assert!(inputs.len() == self.inputs);
+        debug_assert!(outputs.len() == self.size());
+
+        kernel::dense(
+            &self.weights,
+            &self.biases,
+            &self.coefficients,
+            activation,
+            inputs,
+            outputs,
+        );
+    }
+
+    /// For each neuron its bias, coefficient, then the weights of the connections it
+    /// has, skipping masked out ones.
+    pub fn parameters<'a>(&'a self, layer: &'a Layer) -> impl Iterator<Item = f32> + 'a {
+        (0..self.size()).flat_map(move |neuron| {
+            let weights = self
+                .row(neuron)
+                .iter()
+                .zip(connected(layer, neuron))
+                .filter_map(|(&weight, connected)| connected.then_some(weight));
+
+            once(self.biases[neuron])
+                .chain(once(self.coefficients[neuron]))
+                .chain(weights)
+        })
+    }
+
+    /// Zeroes the weights of masked out connections, so they have no effect.
+    pub fn disconnect(&mut self, layer: &Layer) {
+        for neuron in 0..self.size() {
+            let row = &mut self.weights[neuron * self.inputs..][..self.inputs];
+
+            for (weight, _) in row
+                .iter_mut()
+                .zip(connected(layer, neuron))
+                .filter(|(_, connected)| !connected)
+            {
+                *weight = 0.0;
+            }
+        }
+    }
+}
+
+/// Whether each of a neuron's inputs is connected, as given by its layer's mask.
+/// Recurrent inputs are never masked.
+fn connected(layer: &Layer, neuron: usize) -> impl Iterator<Item = bool> + '_ {
+    layer
+        .connections(neuron)
+        .into_iter()
+        .flatten()
+        .copied()
+        .chain(repeat(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Source;
+    use approx::*;
+
+    fn layer(size: usize) -> Layer {
+        Layer::new(size, vec![Source::Input(0)])
+    }
+
+    #[test]
+    fn random() {
+        // Because we always use the same seed, our `rng` in here will
+        // always return the same set of values
+        let rng = PetriRand::with_seed(Default::default());
+        let parameters = LayerParameters::random(&rng, &layer(1), 4);
+
+        assert_relative_eq!(parameters.biases[0], 0.7654091);
+        assert_relative_eq!(parameters.coefficients[0], 0.6088588);
+        assert_relative_eq!(
+            parameters.row(0),
+            [-0.77652967, -0.2356317, -0.63342357, 0.10133362].as_ref()
+        );
+    }
+
+    #[test]
+    fn propagate() {
+        let parameters =
+            LayerParameters::from_weights(&layer(1), 2, &mut vec![0.5, 0.1, -0.3, 0.8].into_iter());
+        let mut output = [0.0];
+
+        // Ensures our PReLU works for < 0.0 cases:
+        parameters.propagate(&[-10.0, -10.0], Activation::PRelu, &mut output);
+        assert_relative_eq!(output[0], ((-0.3 * -10.0) + (0.8 * -10.0) + 0.5) * 0.1);
+
+        // `0.5` and `1.0` chosen by a fair dice roll:
+        parameters.propagate(&[0.5, 1.0], Activation::PRelu, &mut output);
+        assert_relative_eq!(output[0], (-0.3 * 0.5) + (0.8 * 1.0) + 0.5);
+    }
+
+    #[test]
+    fn from_weights() {
+        let weights: Vec<f32> = vec![0.5, 0.1, 0.9, 0.8, -0.1, -0.5, 0.2, 0.3, 0.4, 0.6];
+
+        let parameters = LayerParameters::from_weights(&layer(2), 3, &mut weights.into_iter());
+
+        assert_eq!(parameters.size(), 2);
+        assert_relative_eq!(parameters.biases.as_slice(), [0.5, -0.5].as_ref());
+        assert_relative_eq!(parameters.coefficients.as_slice(), [0.1, 0.2].as_ref());
+        assert_relative_eq!(parameters.row(0), [0.9, 0.8, -0.1].as_ref());
+        assert_relative_eq!(parameters.row(1), [0.3, 0.4, 0.6].as_ref());
+    }
+
+    #[test]
+    fn masked() {
+        let layer = layer(2).with_mask(vec![vec![true, false], vec![false, true]]);
+        let weights = vec![0.5, 0.1, 0.9, -0.5, 0.2, 0.3];
+
+        let parameters = LayerParameters::from_weights(&layer, 2, &mut weights.clone().into_iter());
+
+        assert_relative_eq!(parameters.row(0), [0.9, 0.0].as_ref());
+        assert_relative_eq!(parameters.row(1), [0.0, 0.3].as_ref());
+        assert!(parameters.parameters(&layer).eq(weights));
+
+        let rng = PetriRand::with_seed(Default::default());
+        let parameters = LayerParameters::random(&rng, &layer, 2);
+
+        assert_eq!(parameters.row(0)[1], 0.0);
+        assert_eq!(parameters.row(1)[0], 0.0);
+    }
+}
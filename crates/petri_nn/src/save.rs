@@ -0,0 +1,181 @@
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+use crate::{Network, NetworkError, Topology};
+
+/// Starts every network saved in the binary format.
+pub const MAGIC: [u8; 4] = *b"PTNN";
+
+/// Version of the binary format written by `Network::to_bytes`, which follows the
+/// magic bytes as a little-endian `u16`. Bump it whenever `SavedNetwork` changes.
+pub const FORMAT_VERSION: u16 = 1;
+
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+/// What a `Network` is saved as: its topology, including each layer's activation,
+/// and its parameters laid out as in `Network::weights()`.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SavedNetwork {
+    topology: Topology,
+    weights: Vec<f32>,
+}
+
+impl From<Network> for SavedNetwork {
+    fn from(network: Network) -> Self {
+        Self {
+            weights: network.weights().collect(),
+            topology: network.topology,
+        }
+    }
+}
+
+impl TryFrom<SavedNetwork> for Network {
+    type Error = NetworkError;
+
+    fn try_from(mut saved: SavedNetwork) -> Result<Self, Self::Error> {
+        // Hand written files can leave out the same fields as topology configs
+        saved.topology.fill_defaults();
+
+        Network::try_from_weights(saved.topology, saved.weights)
+    }
+}
+
+impl Network {
+    /// Saves the network as human readable RON.
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, Default::default()).unwrap()
+    }
+
+    /// Loads a network saved by `to_ron`.
+    pub fn from_ron(ron: &str) -> Result<Self, NetworkError> {
+        let saved: SavedNetwork =
+            ron::from_str(ron).map_err(|error| NetworkError::Format(error.to_string()))?;
+
+        saved.try_into()
+    }
+
+    /// Saves the network in the compact binary format: the `MAGIC` bytes and the
+    /// `FORMAT_VERSION`, followed by the network encoded with bincode's
+    /// default options, so with variable length integers.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + 4 * self.topology.parameter_count());
+
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bincode::DefaultOptions::new()
+            .serialize_into(&mut bytes, self)
+            .unwrap();
+
+        bytes
+    }
+
+    /// Loads a network saved by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NetworkError> {
+        if bytes.len() < HEADER_LEN || bytes[..MAGIC.len()] != MAGIC {
+            return Err(NetworkError::NotANetwork);
+        }
+
+        let version = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]);
+
+        if version != FORMAT_VERSION {
+            return Err(NetworkError::UnsupportedVersion { version });
+        }
+
+        let saved: SavedNetwork = bincode::DefaultOptions::new()
+            .deserialize(&bytes[HEADER_LEN..])
+            .map_err(|error| NetworkError::Format(error.to_string()))?;
+
+        saved.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Activation, LayerKind, Source, TopologyError};
+    use petri_rand::*;
+
+    fn network() -> Network {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let mut topology = Topology::dense(&[3, 4, 2]);
+        topology.inputs.push(1);
+        topology.layers[0].kind = LayerKind::Elman;
+        topology.layers[1].sources.push(Source::Input(1));
+        topology.layers[1].mask = Some(vec![vec![true, false, true, true, true]; 2]);
+        topology.outputs.push(0);
+
+        Network::random(&rng, topology)
+            .with_activations(vec![Activation::Gaussian, Activation::Tanh])
+    }
+
+    fn assert_same(loaded: &Network, network: &Network) {
+        assert_eq!(loaded.topology(), network.topology());
+        assert!(loaded.weights().eq(network.weights()));
+        assert_eq!(
+            loaded.propagate(vec![0.1, 0.2, 0.3, 0.4]),
+            network.propagate(vec![0.1, 0.2, 0.3, 0.4])
+        );
+    }
+
+    #[test]
+    fn ron() {
+        let network = network();
+
+        assert_same(&Network::from_ron(&network.to_ron()).unwrap(), &network);
+
+        // Fields can be left out as in topology configs
+        let loaded = Network::from_ron(
+            "(
+                topology: (inputs: [1], layers: [(size: 1, activation: Identity)]),
+                weights: [0.5, 0.1, 2.0],
+            )",
+        )
+        .unwrap();
+
+        assert_eq!(loaded.propagate(vec![3.0]), vec![6.5]);
+
+        assert!(matches!(
+            Network::from_ron("(topology: ("),
+            Err(NetworkError::Format(_))
+        ));
+        assert_eq!(
+            Network::from_ron("(topology: (inputs: [1], layers: []), weights: [])").unwrap_err(),
+            NetworkError::Topology(TopologyError::NoLayers)
+        );
+    }
+
+    #[test]
+    fn json() {
+        let network = network();
+        let json = serde_json::to_string(&network).unwrap();
+
+        assert_same(&serde_json::from_str(&json).unwrap(), &network);
+    }
+
+    #[test]
+    fn bytes() {
+        let network = network();
+        let bytes = network.to_bytes();
+
+        assert_eq!(bytes[..4], MAGIC);
+        assert_same(&Network::from_bytes(&bytes).unwrap(), &network);
+
+        assert_eq!(
+            Network::from_bytes(b"PNG").unwrap_err(),
+            NetworkError::NotANetwork
+        );
+
+        let mut newer = bytes.clone();
+        newer[4] = 2;
+
+        assert_eq!(
+            Network::from_bytes(&newer).unwrap_err(),
+            NetworkError::UnsupportedVersion { version: 2 }
+        );
+        assert!(matches!(
+            Network::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(NetworkError::Format(_))
+        ));
+    }
+}
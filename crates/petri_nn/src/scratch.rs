@@ -0,0 +1,35 @@
+#[cfg(feature = "bevy")]
+use bevy::ecs::component::Component;
+
+/// Reusable buffers for `Network::propagate_into`, so that propagating doesn't
+/// allocate once they have grown to fit the network. Get one sized up front with
+/// `Network::scratch`, and keep it around between propagations.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "bevy", derive(Component))]
+pub struct Scratch {
+    /// The outputs of every layer, one after another.
+    pub(crate) values: Vec<f32>,
+    /// The inputs of a layer that draws from several sources or is recurrent.
+    pub(crate) gathered: Vec<f32>,
+    /// The output heads one after another, when there are several.
+    pub(crate) outputs: Vec<f32>,
+}
+
+impl Scratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grows the buffers to the given lengths. Only allocates when they're too short.
+    pub(crate) fn fit(&mut self, values: usize, gathered: usize, outputs: usize) {
+        for (buffer, len) in [
+            (&mut self.values, values),
+            (&mut self.gathered, gathered),
+            (&mut self.outputs, outputs),
+        ] {
+            if buffer.len() < len {
+                buffer.resize(len, 0.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(feature = "bevy")]
+use bevy::ecs::component::Component;
+
+use crate::{LayerKind, Topology};
+
+/// The outputs of a network's recurrent layers from its last propagation, which
+/// they take as extra inputs on the next one. Kept apart from the `Network` so the
+/// same network can run several independent sequences.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(Component))]
+pub struct HiddenState {
+    /// Indexed by layer, and empty for the layers that aren't recurrent.
+    layers: Vec<Vec<f32>>,
+}
+
+impl HiddenState {
+    pub(crate) fn new(topology: &Topology) -> Self {
+        Self {
+            layers: topology
+                .layers
+                .iter()
+                .map(|layer| match layer.kind {
+                    LayerKind::Dense => Vec::new(),
+                    LayerKind::Elman => vec![0.0; layer.size],
+                })
+                .collect(),
+        }
+    }
+
+    /// Forgets everything, as if the network had never been propagated.
+    pub fn reset(&mut self) {
+        self.layers.iter_mut().for_each(|layer| layer.fill(0.0));
+    }
+
+    pub fn layer(&self, index: usize) -> &[f32] {
+        &self.layers[index]
+    }
+
+    pub(crate) fn layer_mut(&mut self, index: usize) -> &mut [f32] {
+        &mut self.layers[index]
+    }
+
+    pub(crate) fn fits(&self, topology: &Topology) -> bool {
+        self.layers.len() == topology.layers.len()
+            && self
+                .layers
+                .iter()
+                .zip(&topology.layers)
+                .all(|(state, layer)| {
+                    state.len()
+                        == match layer.kind {
+                            LayerKind::Dense => 0,
+                            LayerKind::Elman => layer.size,
+                        }
+                })
+    }
+}
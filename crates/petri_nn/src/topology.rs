@@ -0,0 +1,440 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Activation;
+
+/// Describes how a network's layers are wired together: any number of input
+/// heads, layers that can draw from any inputs or earlier layers (allowing skip
+/// connections), optional sparse connection masks, and any number of output heads.
+///
+/// A `Vec<usize>` of layer sizes converts into the plain dense feed-forward
+/// topology, with the first size being the single input head.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Topology {
+    /// Sizes of the input heads, which are concatenated when propagating.
+    pub inputs: Vec<usize>,
+    pub layers: Vec<Layer>,
+    /// Indices of the layers read out as output heads. Left empty in a config
+    /// file, it defaults to the last layer.
+    #[serde(default)]
+    pub outputs: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layer {
+    pub size: usize,
+    #[serde(default)]
+    pub kind: LayerKind,
+    #[serde(default)]
+    pub activation: Activation,
+    /// The inputs of every neuron of this layer, concatenated in order. Left empty
+    /// in a config file, it defaults to the previous layer, or all the input heads
+    /// for the first layer.
+    #[serde(default)]
+    pub sources: Vec<Source>,
+    /// Which connections exist, as one row per neuron of whether it is connected to
+    /// each of its inputs. Masked out connections have no weight. Fully connected
+    /// when `None`.
+    #[serde(default)]
+    pub mask: Option<Vec<Vec<bool>>>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LayerKind {
+    /// Outputs only depend on the current inputs.
+    #[default]
+    Dense,
+    /// Elman recurrent layer: every neuron also takes the layer's own outputs from
+    /// the previous propagation as inputs, after its sources. Those are kept in a
+    /// `HiddenState`.
+    Elman,
+}
+
+/// Where a layer takes some of its inputs from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Source {
+    /// An input head, by index.
+    Input(usize),
+    /// An earlier layer, by index.
+    Layer(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopologyError {
+    NoInputs,
+    NoLayers,
+    NoOutputs,
+    EmptyInput {
+        input: usize,
+    },
+    EmptyLayer {
+        layer: usize,
+    },
+    NoSources {
+        layer: usize,
+    },
+    /// The source is not an input head, or not a layer before this one.
+    InvalidSource {
+        layer: usize,
+        source: Source,
+    },
+    InvalidOutput {
+        output: usize,
+    },
+    /// The mask doesn't have one row per neuron, each as long as the layer's inputs.
+    InvalidMask {
+        layer: usize,
+        expected: (usize, usize),
+    },
+    /// The config could not be parsed.
+    Config(String),
+}
+
+impl fmt::Display for TopologyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoInputs => write!(f, "topology has no inputs"),
+            Self::NoLayers => write!(f, "topology has no layers"),
+            Self::NoOutputs => write!(f, "topology has no outputs"),
+            Self::EmptyInput { input } => write!(f, "input {} has no neurons", input),
+            Self::EmptyLayer { layer } => write!(f, "layer {} has no neurons", layer),
+            Self::NoSources { layer } => write!(f, "layer {} has no sources", layer),
+            Self::InvalidSource { layer, source } => {
+                write!(f, "layer {} has invalid source {:?}", layer, source)
+            }
+            Self::InvalidOutput { output } => write!(f, "output layer {} does not exist", output),
+            Self::InvalidMask { layer, expected } => write!(
+                f,
+                "mask of layer {} is not {} rows of {} connections",
+                layer, expected.0, expected.1
+            ),
+            Self::Config(error) => write!(f, "invalid topology config: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for TopologyError {}
+
+impl Layer {
+    /// A fully connected layer with the default activation.
+    pub fn new(size: usize, sources: Vec<Source>) -> Self {
+        Self {
+            size,
+            kind: LayerKind::default(),
+            activation: Activation::default(),
+            sources,
+            mask: None,
+        }
+    }
+
+    pub fn with_kind(mut self, kind: LayerKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    pub fn with_mask(mut self, mask: Vec<Vec<bool>>) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Whether each of the neuron's inputs is connected.
+    pub(crate) fn connections(&self, neuron: usize) -> Option<&[bool]> {
+        self.mask.as_ref().map(|mask| mask[neuron].as_slice())
+    }
+}
+
+impl Topology {
+    /// A dense feed-forward topology, as `inputs` followed by each layer's size. With
+    /// no sizes at all, the topology is empty and fails to validate with `NoInputs`.
+    pub fn dense(sizes: &[usize]) -> Self {
+        let (inputs, layers) = match sizes.split_first() {
+            Some(split) => split,
+            None => {
+                return Self {
+                    inputs: Vec::new(),
+                    layers: Vec::new(),
+                    outputs: Vec::new(),
+                }
+            }
+        };
+
+        Self {
+            inputs: vec![*inputs],
+            layers: layers
+                .iter()
+                .enumerate()
+                .map(|(index, &size)| {
+                    let source = match index {
+                        0 => Source::Input(0),
+                        _ => Source::Layer(index - 1),
+                    };
+
+                    Layer::new(size, vec![source])
+                })
+                .collect(),
+            outputs: vec![layers.len().saturating_sub(1)],
+        }
+    }
+
+    /// Parses and validates a topology written in RON, filling in the defaults of
+    /// any fields left out.
+    pub fn from_config(config: &str) -> Result<Self, TopologyError> {
+        let mut topology: Self =
+            ron::from_str(config).map_err(|error| TopologyError::Config(error.to_string()))?;
+
+        topology.fill_defaults();
+        topology.validate()?;
+
+        Ok(topology)
+    }
+
+    /// Fills in the sources and outputs left empty, as described on the fields.
+    pub(crate) fn fill_defaults(&mut self) {
+        for index in 0..self.layers.len() {
+            if self.layers[index].sources.is_empty() {
+                self.layers[index].sources = match index {
+                    0 => (0..self.inputs.len()).map(Source::Input).collect(),
+                    _ => vec![Source::Layer(index - 1)],
+                };
+            }
+        }
+
+        if self.outputs.is_empty() && !self.layers.is_empty() {
+            self.outputs.push(self.layers.len() - 1);
+        }
+    }
+
+    pub fn to_config(&self) -> String {
+        ron::ser::to_string_pretty(self, Default::default()).unwrap()
+    }
+
+    pub fn validate(&self) -> Result<(), TopologyError> {
+        if self.inputs.is_empty() {
+            return Err(TopologyError::NoInputs);
+        }
+        if self.layers.is_empty() {
+            return Err(TopologyError::NoLayers);
+        }
+        if self.outputs.is_empty() {
+            return Err(TopologyError::NoOutputs);
+        }
+
+        if let Some(input) = self.inputs.iter().position(|&size| size == 0) {
+            return Err(TopologyError::EmptyInput { input });
+        }
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            if layer.size == 0 {
+                return Err(TopologyError::EmptyLayer { layer: index });
+            }
+            if layer.sources.is_empty() {
+                return Err(TopologyError::NoSources { layer: index });
+            }
+
+            // Only earlier layers are allowed, which also rules out cycles
+            let invalid = layer.sources.iter().find(|source| match **source {
+                Source::Input(input) => input >= self.inputs.len(),
+                Source::Layer(source) => source >= index,
+            });
+
+            if let Some(&source) = invalid {
+                return Err(TopologyError::InvalidSource {
+                    layer: index,
+                    source,
+                });
+            }
+
+            if let Some(mask) = &layer.mask {
+                let fan_in = self.fan_in(index);
+
+                if mask.len() != layer.size || mask.iter().any(|row| row.len() != fan_in) {
+                    return Err(TopologyError::InvalidMask {
+                        layer: index,
+                        expected: (layer.size, fan_in),
+                    });
+                }
+            }
+        }
+
+        if let Some(&output) = self.outputs.iter().find(|&&o| o >= self.layers.len()) {
+            return Err(TopologyError::InvalidOutput { output });
+        }
+
+        Ok(())
+    }
+
+    /// Total size of all the input heads.
+    pub fn input_size(&self) -> usize {
+        self.inputs.iter().sum()
+    }
+
+    /// Total size of all the output heads.
+    pub fn output_size(&self) -> usize {
+        self.outputs.iter().map(|&o| self.layers[o].size).sum()
+    }
+
+    /// Number of inputs of each neuron of a layer.
+    pub fn fan_in(&self, layer: usize) -> usize {
+        self.layers[layer]
+            .sources
+            .iter()
+            .map(|&source| self.source_size(source))
+            .sum()
+    }
+
+    /// Number of weights of each neuron of a layer: one per input, plus one per
+    /// neuron of the layer itself if it is recurrent.
+    pub fn neuron_inputs(&self, layer: usize) -> usize {
+        match self.layers[layer].kind {
+            LayerKind::Dense => self.fan_in(layer),
+            LayerKind::Elman => self.fan_in(layer) + self.layers[layer].size,
+        }
+    }
+
+    /// Number of parameters of a network with this topology: every neuron's bias and
+    /// coefficient, and a weight for each connection it has.
+    pub fn parameter_count(&self) -> usize {
+        (0..self.layers.len())
+            .map(|layer| self.layer_parameter_count(layer))
+            .sum()
+    }
+
+    /// Number of parameters of a layer, as counted by `parameter_count`.
+    pub fn layer_parameter_count(&self, layer: usize) -> usize {
+        let masked = self.layers[layer]
+            .mask
+            .iter()
+            .flatten()
+            .flatten()
+            .filter(|&&connected| !connected)
+            .count();
+
+        self.layers[layer].size * (2 + self.neuron_inputs(layer)) - masked
+    }
+
+    /// Whether both topologies have the same inputs, layers and outputs, whatever the
+    /// activations of their layers.
+    pub(crate) fn same_shape(&self, other: &Topology) -> bool {
+        self.inputs == other.inputs
+            && self.outputs == other.outputs
+            && self.layers.len() == other.layers.len()
+            && self.layers.iter().zip(&other.layers).all(|(a, b)| {
+                a.size == b.size && a.kind == b.kind && a.sources == b.sources && a.mask == b.mask
+            })
+    }
+
+    pub(crate) fn source_size(&self, source: Source) -> usize {
+        match source {
+            Source::Input(input) => self.inputs[input],
+            Source::Layer(layer) => self.layers[layer].size,
+        }
+    }
+}
+
+impl From<Vec<usize>> for Topology {
+    fn from(sizes: Vec<usize>) -> Self {
+        Self::dense(&sizes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense() {
+        let topology = Topology::from(vec![4, 3, 2]);
+
+        assert_eq!(topology.validate(), Ok(()));
+        assert_eq!(topology.inputs, vec![4]);
+        assert_eq!(topology.layers[1].sources, vec![Source::Layer(0)]);
+        assert_eq!(topology.outputs, vec![1]);
+        assert_eq!(topology.fan_in(1), 3);
+    }
+
+    #[test]
+    fn parameter_count() {
+        assert_eq!(Topology::dense(&[4, 3, 2]).parameter_count(), 3 * 6 + 2 * 5);
+
+        // Recurrent weights count, masked out ones don't
+        let mut topology = Topology::dense(&[2, 2]);
+        topology.layers[0].kind = LayerKind::Elman;
+        topology.layers[0].mask = Some(vec![vec![true, false], vec![true, true]]);
+
+        assert_eq!(topology.parameter_count(), 2 * 6 - 1);
+    }
+
+    #[test]
+    fn validate() {
+        let mut topology = Topology::dense(&[4, 3, 2]);
+        topology.layers[0].sources.push(Source::Layer(1));
+
+        assert_eq!(
+            topology.validate(),
+            Err(TopologyError::InvalidSource {
+                layer: 0,
+                source: Source::Layer(1)
+            })
+        );
+
+        let mut topology = Topology::dense(&[4, 3, 2]);
+        topology.layers[1].mask = Some(vec![vec![true; 3]; 3]);
+
+        assert_eq!(
+            topology.validate(),
+            Err(TopologyError::InvalidMask {
+                layer: 1,
+                expected: (2, 3)
+            })
+        );
+
+        assert_eq!(
+            Topology::dense(&[4]).validate(),
+            Err(TopologyError::NoLayers)
+        );
+    }
+
+    #[test]
+    fn config() {
+        let topology = Topology::from_config(
+            "(
+                inputs: [4, 2],
+                layers: [
+                    (size: 3),
+                    (size: 2, activation: Tanh, sources: [Layer(0), Input(1)]),
+                    (size: 1, kind: Elman, mask: Some([[true, false]])),
+                ],
+                outputs: [1, 2],
+            )",
+        )
+        .unwrap();
+
+        assert_eq!(
+            topology.layers[0].sources,
+            vec![Source::Input(0), Source::Input(1)]
+        );
+        assert_eq!(topology.layers[1].activation, Activation::Tanh);
+        assert_eq!(topology.fan_in(1), 5);
+        assert_eq!(topology.layers[2].sources, vec![Source::Layer(1)]);
+        assert_eq!(topology.layers[2].kind, LayerKind::Elman);
+        assert_eq!(topology.neuron_inputs(2), 3);
+        assert_eq!(topology.output_size(), 3);
+
+        assert_eq!(Topology::from_config(&topology.to_config()), Ok(topology));
+
+        assert!(matches!(
+            Topology::from_config("(inputs: [4], layers: [(size: 0)])"),
+            Err(TopologyError::EmptyLayer { layer: 0 })
+        ));
+        assert!(matches!(
+            Topology::from_config("(inputs: [4]"),
+            Err(TopologyError::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,487 @@
+use crate::{parameters::LayerParameters, LayerKind, Network, Source};
+
+/// How a `Trainer` turns gradients into parameter updates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Optimizer {
+    /// Plain gradient descent, stepping each parameter against its gradient.
+    Sgd { learning_rate: f32 },
+    /// Adam, scaling each parameter's step by running estimates of the mean and
+    /// variance of its gradient.
+    Adam {
+        learning_rate: f32,
+        beta1: f32,
+        beta2: f32,
+        epsilon: f32,
+    },
+}
+
+impl Optimizer {
+    pub fn sgd(learning_rate: f32) -> Self {
+        Self::Sgd { learning_rate }
+    }
+
+    /// Adam with the usual defaults for everything but the learning rate.
+    pub fn adam(learning_rate: f32) -> Self {
+        Self::Adam {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+        }
+    }
+}
+
+/// Trains a network by backpropagation, minimising the mean squared error between
+/// its outputs and the targets of input/target pairs.
+///
+/// Every layer kind and activation can be trained, with some limits: recurrent layers
+/// are trained as `Network::propagate` runs them, from a zeroed hidden state, so their
+/// recurrent weights don't change, and masked out connections stay disconnected.
+///
+/// Adam keeps state for the network it trains, so use a trainer for one network
+/// only. It starts over if given a network of another shape.
+#[derive(Debug, Clone)]
+pub struct Trainer {
+    optimizer: Optimizer,
+    batch_size: usize,
+    /// Adam's estimates of each parameter's gradient mean and variance.
+    moments: Vec<(LayerParameters, LayerParameters)>,
+    steps: i32,
+}
+
+/// The values of one propagation, kept for backpropagation.
+struct Pass {
+    /// The inputs of each layer, gathered from its sources.
+    inputs: Vec<Vec<f32>>,
+    /// The weighted sum of each neuron, before its activation.
+    sums: Vec<Vec<f32>>,
+    outputs: Vec<Vec<f32>>,
+}
+
+impl Trainer {
+    /// A trainer updating the network after every sample.
+    pub fn new(optimizer: Optimizer) -> Self {
+        Self {
+            optimizer,
+            batch_size: 1,
+            moments: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    /// Averages the gradients of this many samples for each update.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "Batch size must be at least 1");
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Trains on every sample once, in order, returning their mean loss as they were
+    /// before their update. Each sample is the concatenated input heads, and the
+    /// targets of the concatenated output heads.
+    pub fn train<I, T>(&mut self, network: &mut Network, samples: &[(I, T)]) -> f32
+    where
+        I: AsRef<[f32]>,
+        T: AsRef<[f32]>,
+    {
+        let mut total = 0.0;
+
+        for batch in samples.chunks(self.batch_size) {
+            let (mut gradients, loss) = gradients(network, batch);
+
+            self.step(network, &mut gradients, batch.len());
+            total += loss;
+        }
+
+        total / samples.len().max(1) as f32
+    }
+
+    fn step(&mut self, network: &mut Network, gradients: &mut [LayerParameters], samples: usize) {
+        let scale = 1.0 / samples as f32;
+
+        // Masked out connections have no weight to train
+        for (layer, gradients) in network.topology.layers.iter().zip(gradients.iter_mut()) {
+            gradients.disconnect(layer);
+        }
+
+        let gradients = gradients.iter().flat_map(|layer| layer.values());
+
+        match self.optimizer {
+            Optimizer::Sgd { learning_rate } => {
+                let parameters = network
+                    .layers
+                    .iter_mut()
+                    .flat_map(|layer| layer.values_mut());
+
+                for (parameter, gradient) in parameters.zip(gradients) {
+                    *parameter -= learning_rate * gradient * scale;
+                }
+            }
+            Optimizer::Adam {
+                learning_rate,
+                beta1,
+                beta2,
+                epsilon,
+            } => {
+                let fits = self.moments.len() == network.layers.len()
+                    && self
+                        .moments
+                        .iter()
+                        .zip(&network.layers)
+                        .all(|((mean, _), layer)| mean.same_shape(layer));
+
+                if !fits {
+                    self.moments = network
+                        .layers
+                        .iter()
+                        .map(|layer| (layer.zeroed(), layer.zeroed()))
+                        .collect();
+                    self.steps = 0;
+                }
+
+                self.steps += 1;
+
+                let mean_correction = 1.0 - beta1.powi(self.steps);
+                let variance_correction = 1.0 - beta2.powi(self.steps);
+
+                let moments = self
+                    .moments
+                    .iter_mut()
+                    .flat_map(|(mean, variance)| mean.values_mut().zip(variance.values_mut()));
+
+                let parameters = network
+                    .layers
+                    .iter_mut()
+                    .flat_map(|layer| layer.values_mut());
+
+                for ((parameter, gradient), (mean, variance)) in
+                    parameters.zip(gradients).zip(moments)
+                {
+                    let gradient = gradient * scale;
+
+                    *mean = beta1 * *mean + (1.0 - beta1) * gradient;
+                    *variance = beta2 * *variance + (1.0 - beta2) * gradient * gradient;
+
+                    *parameter -= learning_rate * (*mean / mean_correction)
+                        / ((*variance / variance_correction).sqrt() + epsilon);
+                }
+            }
+        }
+    }
+}
+
+/// The mean squared error of the network's outputs against the targets, over every
+/// sample.
+pub fn mean_squared_error<I, T>(network: &Network, samples: &[(I, T)]) -> f32
+where
+    I: AsRef<[f32]>,
+    T: AsRef<[f32]>,
+{
+    let total: f32 = samples
+        .iter()
+        .map(|(inputs, targets)| {
+            let outputs = network.propagate(inputs.as_ref().to_vec());
+            squared_error(&outputs, targets.as_ref())
+        })
+        .sum();
+
+    total / samples.len().max(1) as f32
+}
+
+fn squared_error(outputs: &[f32], targets: &[f32]) -> f32 {
+    assert!(
+        outputs.len() == targets.len(),
+        "Expected one target per output"
+    );
+
+    outputs
+        .iter()
+        .zip(targets)
+        .map(|(output, target)| (output - target).powi(2))
+        .sum::<f32>()
+        / outputs.len() as f32
+}
+
+/// The summed gradients of the loss of every sample, shaped like the network's
+/// layers, and the summed loss.
+fn gradients<I, T>(network: &Network, samples: &[(I, T)]) -> (Vec<LayerParameters>, f32)
+where
+    I: AsRef<[f32]>,
+    T: AsRef<[f32]>,
+{
+    let topology = &network.topology;
+    let mut gradients: Vec<LayerParameters> =
+        network.layers.iter().map(LayerParameters::zeroed).collect();
+    let mut loss = 0.0;
+
+    for (inputs, targets) in samples {
+        let pass = forward(network, inputs.as_ref());
+        let targets = targets.as_ref();
+
+        // The loss gradient of every layer's outputs, starting from the output heads
+        let mut output_gradients: Vec<Vec<f32>> = topology
+            .layers
+            .iter()
+            .map(|layer| vec![0.0; layer.size])
+            .collect();
+
+        let outputs: Vec<f32> = topology
+            .outputs
+            .iter()
+            .flat_map(|&output| pass.outputs[output].iter().copied())
+            .collect();
+
+        loss += squared_error(&outputs, targets);
+
+        let mut targets = targets.iter();
+
+        for &output in &topology.outputs {
+            for (gradient, value) in output_gradients[output]
+                .iter_mut()
+                .zip(&pass.outputs[output])
+            {
+                *gradient += 2.0 * (value - targets.next().unwrap()) / outputs.len() as f32;
+            }
+        }
+
+        for (index, layer) in topology.layers.iter().enumerate().rev() {
+            let parameters = &network.layers[index];
+            let layer_gradients = &mut gradients[index];
+            let outputs = std::mem::take(&mut output_gradients[index]);
+            let mut input_gradients = vec![0.0; parameters.inputs()];
+
+            for (neuron, &output) in outputs.iter().enumerate() {
+                let sum = pass.sums[index][neuron];
+                let coefficient = parameters.coefficients()[neuron];
+                let delta = output * layer.activation.derivative(sum, coefficient);
+
+                layer_gradients.biases_mut()[neuron] += delta;
+                layer_gradients.coefficients_mut()[neuron] +=
+                    output * layer.activation.coefficient_derivative(sum);
+
+                for (((gradient, input), weight), input_gradient) in layer_gradients
+                    .row_mut(neuron)
+                    .iter_mut()
+                    .zip(&pass.inputs[index])
+                    .zip(parameters.row(neuron))
+                    .zip(&mut input_gradients)
+                {
+                    *gradient += delta * input;
+                    *input_gradient += delta * weight;
+                }
+            }
+
+            // Hand the gradients of the inputs back to the layers they came from
+            let mut from = 0;
+
+            for &source in &layer.sources {
+                let size = topology.source_size(source);
+
+                if let Source::Layer(earlier) = source {
+                    for (gradient, input_gradient) in output_gradients[earlier]
+                        .iter_mut()
+                        .zip(&input_gradients[from..][..size])
+                    {
+                        *gradient += input_gradient;
+                    }
+                }
+
+                from += size;
+            }
+        }
+    }
+
+    (gradients, loss)
+}
+
+/// Propagates like `Network::propagate`, keeping every layer's values.
+fn forward(network: &Network, inputs: &[f32]) -> Pass {
+    let topology = &network.topology;
+
+    assert!(
+        inputs.len() == topology.input_size(),
+        "Expected {} inputs",
+        topology.input_size()
+    );
+
+    let mut pass = Pass {
+        inputs: Vec::with_capacity(topology.layers.len()),
+        sums: Vec::with_capacity(topology.layers.len()),
+        outputs: Vec::with_capacity(topology.layers.len()),
+    };
+
+    for (layer, parameters) in topology.layers.iter().zip(&network.layers) {
+        let mut gathered = Vec::with_capacity(parameters.inputs());
+
+        for &source in &layer.sources {
+            match source {
+                Source::Input(input) => gathered.extend_from_slice(
+                    &inputs[network.layout.inputs[input]..][..topology.inputs[input]],
+                ),
+                Source::Layer(earlier) => gathered.extend_from_slice(&pass.outputs[earlier]),
+            }
+        }
+
+        // As with `Network::propagate`, recurrent layers start from a zeroed state
+        if layer.kind == LayerKind::Elman {
+            gathered.resize(gathered.len() + layer.size, 0.0);
+        }
+
+        let sums: Vec<f32> = (0..layer.size)
+            .map(|neuron| {
+                parameters.biases()[neuron]
+                    + parameters
+                        .row(neuron)
+                        .iter()
+                        .zip(&gathered)
+                        .map(|(weight, input)| weight * input)
+                        .sum::<f32>()
+            })
+            .collect();
+
+        let outputs = sums
+            .iter()
+            .zip(parameters.coefficients())
+            .map(|(&sum, &coefficient)| layer.activation.apply(sum, coefficient))
+            .collect();
+
+        pass.inputs.push(gathered);
+        pass.sums.push(sums);
+        pass.outputs.push(outputs);
+    }
+
+    pass
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Activation, Layer, Topology};
+    use approx::*;
+    use petri_rand::*;
+
+    #[test]
+    fn gradients() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        // A skip connection, a mask, a recurrent layer and two output heads
+        let topology = Topology {
+            inputs: vec![3, 2],
+            layers: vec![
+                Layer::new(4, vec![Source::Input(0)])
+                    .with_kind(LayerKind::Elman)
+                    .with_activation(Activation::Tanh),
+                Layer::new(3, vec![Source::Layer(0), Source::Input(1)])
+                    .with_activation(Activation::Softsign)
+                    .with_mask(vec![vec![true, false, true, true, true, true]; 3]),
+                Layer::new(2, vec![Source::Layer(1), Source::Layer(0)])
+                    .with_activation(Activation::PRelu),
+            ],
+            outputs: vec![2, 1],
+        };
+
+        let network = Network::random(&rng, topology);
+        let samples = vec![
+            (
+                vec![0.5, -0.2, 0.9, 0.1, -0.7],
+                vec![0.3, -0.1, 0.2, 0.4, -0.5],
+            ),
+            (
+                vec![-0.4, 0.6, 0.2, 0.8, 0.3],
+                vec![-0.2, 0.5, 0.1, -0.3, 0.6],
+            ),
+        ];
+
+        let (gradients, loss) = super::gradients(&network, &samples);
+
+        assert_relative_eq!(
+            loss / 2.0,
+            mean_squared_error(&network, &samples),
+            epsilon = 1e-5
+        );
+
+        // Against the finite differences of each parameter, in the layout of `weights()`
+        let analytic: Vec<f32> = network
+            .topology
+            .layers
+            .iter()
+            .zip(&gradients)
+            .flat_map(|(layer, gradients)| gradients.parameters(layer))
+            .collect();
+
+        let weights: Vec<f32> = network.weights().collect();
+        let h = 1e-2;
+
+        for (index, &gradient) in analytic.iter().enumerate() {
+            let mut nudged = network.clone();
+            let mut loss = |delta: f32| {
+                let mut weights = weights.clone();
+                weights[index] += delta;
+                nudged.adjust_weights(weights);
+                mean_squared_error(&nudged, &samples) * 2.0
+            };
+
+            let numeric = (loss(h) - loss(-h)) / (2.0 * h);
+
+            assert_relative_eq!(gradient, numeric, epsilon = 2e-3, max_relative = 2e-2);
+        }
+    }
+
+    fn xor() -> Vec<([f32; 2], [f32; 1])> {
+        vec![
+            ([0.0, 0.0], [0.0]),
+            ([0.0, 1.0], [1.0]),
+            ([1.0, 0.0], [1.0]),
+            ([1.0, 1.0], [0.0]),
+        ]
+    }
+
+    fn train(optimizer: Optimizer, epochs: usize) -> (f32, f32) {
+        let rng = PetriRand::with_seed(Default::default());
+        let samples = xor();
+
+        let mut network = Network::random(&rng, vec![2, 8, 1])
+            .with_activations(vec![Activation::Tanh, Activation::Identity]);
+        let mut trainer = Trainer::new(optimizer).with_batch_size(4);
+
+        let before = mean_squared_error(&network, &samples);
+
+        for _ in 0..epochs {
+            trainer.train(&mut network, &samples);
+        }
+
+        (before, mean_squared_error(&network, &samples))
+    }
+
+    #[test]
+    fn sgd() {
+        let (before, after) = train(Optimizer::sgd(0.1), 2000);
+
+        assert!(after < before / 10.0, "{} -> {}", before, after);
+        assert!(after < 0.02, "{}", after);
+    }
+
+    #[test]
+    fn adam() {
+        let (before, after) = train(Optimizer::adam(0.01), 1000);
+
+        assert!(after < before / 10.0, "{} -> {}", before, after);
+        assert!(after < 0.02, "{}", after);
+    }
+
+    #[test]
+    fn masked_weights_stay_disconnected() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let mut topology = Topology::dense(&[2, 2]);
+        topology.layers[0].mask = Some(vec![vec![true, false], vec![false, true]]);
+
+        let mut network = Network::random(&rng, topology);
+        let mut trainer = Trainer::new(Optimizer::adam(0.1));
+
+        trainer.train(&mut network, &[([1.0, 1.0], [0.5, -0.5])]);
+
+        assert_eq!(network.layers[0].row(0)[1], 0.0);
+        assert_eq!(network.layers[0].row(1)[0], 0.0);
+    }
+}
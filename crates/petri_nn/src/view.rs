@@ -0,0 +1,181 @@
+use std::ops::Range;
+
+use crate::{parameters::LayerParameters, Activation, Layer};
+
+/// A layer of a `Network` and its parameters, as given by `Network::layers`.
+#[derive(Debug, Clone, Copy)]
+pub struct LayerView<'a> {
+    index: usize,
+    layer: &'a Layer,
+    parameters: &'a LayerParameters,
+    /// Where the layer's parameters start in `Network::weights()`.
+    offset: usize,
+    count: usize,
+}
+
+/// A neuron of a `Network` and its parameters, as given by `LayerView::neurons`.
+#[derive(Debug, Clone, Copy)]
+pub struct NeuronView<'a> {
+    index: usize,
+    layer: &'a Layer,
+    parameters: &'a LayerParameters,
+}
+
+impl<'a> LayerView<'a> {
+    pub(crate) fn new(
+        index: usize,
+        layer: &'a Layer,
+        parameters: &'a LayerParameters,
+        offset: usize,
+        count: usize,
+    ) -> Self {
+        Self {
+            index,
+            layer,
+            parameters,
+            offset,
+            count,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The layer as described in the network's topology.
+    pub fn layer(&self) -> &'a Layer {
+        self.layer
+    }
+
+    pub fn size(&self) -> usize {
+        self.layer.size
+    }
+
+    pub fn activation(&self) -> Activation {
+        self.layer.activation
+    }
+
+    /// Where the layer's parameters are in `Network::weights()`, so also in any genes
+    /// laid out the same way.
+    pub fn parameter_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.count
+    }
+
+    pub fn biases(&self) -> &'a [f32] {
+        self.parameters.biases()
+    }
+
+    pub fn coefficients(&self) -> &'a [f32] {
+        self.parameters.coefficients()
+    }
+
+    pub fn neuron(&self, index: usize) -> NeuronView<'a> {
+        assert!(index < self.size(), "Neuron index out of range");
+
+        NeuronView {
+            index,
+            layer: self.layer,
+            parameters: self.parameters,
+        }
+    }
+
+    pub fn neurons(&self) -> impl ExactSizeIterator<Item = NeuronView<'a>> {
+        let (layer, parameters) = (self.layer, self.parameters);
+
+        (0..self.size()).map(move |index| NeuronView {
+            index,
+            layer,
+            parameters,
+        })
+    }
+}
+
+impl<'a> NeuronView<'a> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn bias(&self) -> f32 {
+        self.parameters.biases()[self.index]
+    }
+
+    pub fn coefficient(&self) -> f32 {
+        self.parameters.coefficients()[self.index]
+    }
+
+    /// The weight of each input, in the order of the layer's sources, followed by the
+    /// recurrent inputs. Masked out connections have a weight of zero.
+    pub fn weights(&self) -> &'a [f32] {
+        self.parameters.row(self.index)
+    }
+
+    /// Whether each of the inputs is connected, as given by the layer's mask. `None`
+    /// when fully connected. Recurrent inputs are always connected.
+    pub fn connections(&self) -> Option<&'a [bool]> {
+        self.layer.connections(self.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::iter::repeat;
+
+    #[test]
+    fn layers() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let mut topology = Topology::dense(&[3, 2, 1]);
+        topology.layers[0].kind = LayerKind::Elman;
+        topology.layers[0].mask = Some(vec![vec![true, false, true], vec![true; 3]]);
+
+        let network = Network::random(&rng, topology)
+            .with_activations(vec![Activation::Tanh, Activation::Sine]);
+        let weights: Vec<f32> = network.weights().collect();
+
+        assert_eq!(network.parameter_count(), weights.len());
+        assert_eq!(network.parameter_count(), 2 * (2 + 3 + 2) - 1 + (2 + 2));
+
+        let layers: Vec<LayerView> = network.layers().collect();
+
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0].parameter_range(), 0..13);
+        assert_eq!(layers[1].parameter_range(), 13..17);
+        assert_eq!(layers[1].activation(), Activation::Sine);
+        assert_eq!(layers[0].layer().kind, LayerKind::Elman);
+
+        // Every neuron's parameters in the same layout as `weights()`
+        for layer in &layers {
+            let mut parameters = Vec::new();
+
+            for neuron in layer.neurons() {
+                let connected = neuron
+                    .connections()
+                    .into_iter()
+                    .flatten()
+                    .chain(repeat(&true));
+
+                parameters.extend([neuron.bias(), neuron.coefficient()]);
+                parameters.extend(
+                    neuron
+                        .weights()
+                        .iter()
+                        .zip(connected)
+                        .filter_map(|(&weight, &connected)| connected.then_some(weight)),
+                );
+            }
+
+            assert_eq!(parameters, weights[layer.parameter_range()]);
+            assert_eq!(
+                layer.biases(),
+                layer.neurons().map(|n| n.bias()).collect::<Vec<_>>()
+            );
+        }
+
+        let neuron = layers[0].neuron(0);
+
+        assert_eq!(neuron.weights().len(), 3 + 2);
+        assert_eq!(neuron.weights()[1], 0.0);
+        assert_eq!(neuron.coefficient(), layers[0].coefficients()[0]);
+    }
+}
@@ -1,20 +1,16 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use fastrand::{Rng as FastRng};
-use wyhash::WyRng;
+use fastrand::Rng as FastRng;
 use petri_rand::*;
 use rand::prelude::*;
+use wyhash::WyRng;
 
 pub fn init_speed(c: &mut Criterion) {
     c.bench_function("petri-rand init", |b| {
-        b.iter(|| {
-            PetriRand::new()
-        });
+        b.iter(PetriRand::new);
     });
 
     c.bench_function("fastrand init", |b| {
-        b.iter(|| {
-            FastRng::new()
-        });
+        b.iter(FastRng::new);
     });
 }
 
@@ -31,6 +27,19 @@ pub fn gen_u32_speed(c: &mut Criterion) {
         });
     });
 
+    c.bench_function("petri-rand xoshiro u32", |b| {
+        let rng =
+            PetriRand::from_generator(Xoshiro256PlusPlus::with_seed(PetriRand::new().get_u64()));
+
+        b.iter(|| {
+            let mut sum = black_box(0u32);
+            for _ in 0..10_000 {
+                sum = sum.wrapping_add(rng.get_u32())
+            }
+            sum
+        });
+    });
+
     c.bench_function("fastrand u32", |b| {
         let rng = FastRng::new();
 
@@ -68,6 +77,19 @@ pub fn gen_u64_speed(c: &mut Criterion) {
         });
     });
 
+    c.bench_function("petri-rand xoshiro u64", |b| {
+        let rng =
+            PetriRand::from_generator(Xoshiro256PlusPlus::with_seed(PetriRand::new().get_u64()));
+
+        b.iter(|| {
+            let mut sum = black_box(0u64);
+            for _ in 0..10_000 {
+                sum = sum.wrapping_add(rng.get_u64())
+            }
+            sum
+        });
+    });
+
     c.bench_function("fastrand u64", |b| {
         let rng = FastRng::new();
 
@@ -157,6 +179,19 @@ pub fn gen_f32_speed(c: &mut Criterion) {
         });
     });
 
+    c.bench_function("petri-rand xoshiro f32", |b| {
+        let rng =
+            PetriRand::from_generator(Xoshiro256PlusPlus::with_seed(PetriRand::new().get_u64()));
+
+        b.iter(|| {
+            let mut sum = black_box(0.0f32);
+            for _ in 0..10_000 {
+                sum += rng.get_f32();
+            }
+            sum
+        });
+    });
+
     c.bench_function("fastrand f32", |b| {
         let rng = FastRng::new();
 
@@ -170,6 +205,47 @@ pub fn gen_f32_speed(c: &mut Criterion) {
     });
 }
 
+pub fn fill_f32_speed(c: &mut Criterion) {
+    c.bench_function("petri-rand f32 normalised repeat_with", |b| {
+        let rng = PetriRand::new();
+
+        b.iter(|| {
+            let values: Vec<f32> = std::iter::repeat_with(|| rng.get_f32_normalised())
+                .take(10_000)
+                .collect();
+            values
+        });
+    });
+
+    c.bench_function("petri-rand fill_f32_range", |b| {
+        let rng = PetriRand::new();
+
+        b.iter(|| {
+            let mut values = vec![0.0f32; 10_000];
+            rng.fill_f32_range(&mut values, -1.0..1.0);
+            values
+        });
+    });
+
+    c.bench_function("petri-rand fill_bytes", |b| {
+        let rng = PetriRand::new();
+        let mut bytes = vec![0u8; 40_000];
+
+        b.iter(|| rng.fill_bytes(black_box(&mut bytes)));
+    });
+
+    c.bench_function("fastrand fill", |b| {
+        let rng = FastRng::new();
+        let mut bytes = vec![0u8; 40_000];
+
+        b.iter(|| {
+            black_box(&mut bytes)
+                .iter_mut()
+                .for_each(|byte| *byte = rng.u8(..))
+        });
+    });
+}
+
 criterion_group!(
     benches,
     init_speed,
@@ -177,6 +253,7 @@ criterion_group!(
     gen_u64_speed,
     gen_usize_speed,
     gen_bool_speed,
-    gen_f32_speed
+    gen_f32_speed,
+    fill_f32_speed
 );
 criterion_main!(benches);
@@ -0,0 +1,77 @@
+//! Lets `PetriRand` drive samplers from the `rand` ecosystem (`rand_distr`,
+//! `statrs`, ...) with the same deterministic stream.
+
+use rand_core::{Error, RngCore, SeedableRng};
+
+use crate::{Generator, PetriRand};
+
+impl<R: Generator> RngCore for PetriRand<R> {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.get_u32()
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.get_u64()
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        PetriRand::fill_bytes(self, dest)
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<R: Generator> SeedableRng for PetriRand<R> {
+    type Seed = [u8; core::mem::size_of::<u64>()];
+
+    #[inline]
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::seed_from_u64(u64::from_le_bytes(seed))
+    }
+
+    /// Matches [`PetriRand::with_seed`], so both APIs yield the same stream.
+    #[inline]
+    fn seed_from_u64(state: u64) -> Self {
+        Self::from_generator(R::with_seed(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn same_stream_as_petri_rand() {
+        let mut compat: PetriRand = SeedableRng::seed_from_u64(5);
+        let rng = PetriRand::with_seed(5);
+
+        assert_eq!(compat.next_u64(), rng.get_u64());
+        assert_eq!(compat.next_u32(), rng.get_u32());
+
+        let mut seeded: PetriRand = SeedableRng::from_seed(5u64.to_le_bytes());
+
+        assert_eq!(seeded.next_u64(), PetriRand::with_seed(5).get_u64());
+    }
+
+    #[test]
+    fn drives_rand_samplers() {
+        let mut rng = PetriRand::with_seed(Default::default());
+
+        let values: Vec<u8> = (0..1000).map(|_| rng.gen_range(0..10)).collect();
+
+        assert!(values.iter().all(|value| *value < 10));
+
+        let mut bytes = [0u8; 13];
+        rng.fill_bytes(&mut bytes);
+
+        assert!(bytes.iter().any(|byte| *byte != 0));
+    }
+}
@@ -0,0 +1,142 @@
+use std::f64::consts::PI;
+
+use crate::{Generator, PetriRand};
+
+impl<R: Generator> PetriRand<R> {
+    /// Uniform value in `(0.0, 1.0]`, safe to feed into `ln`.
+    #[inline]
+    fn get_f64_open(&self) -> f64 {
+        1.0 - self.get_f64()
+    }
+
+    /// Samples a normal (Gaussian) distribution using the Box-Muller transform.
+    #[inline]
+    pub fn normal_f64(&self, mean: f64, std_dev: f64) -> f64 {
+        debug_assert!(std_dev >= 0.0);
+
+        let radius = (-2.0 * self.get_f64_open().ln()).sqrt();
+        let theta = 2.0 * PI * self.get_f64();
+
+        mean + std_dev * radius * theta.cos()
+    }
+
+    #[inline]
+    pub fn normal_f32(&self, mean: f32, std_dev: f32) -> f32 {
+        self.normal_f64(mean as f64, std_dev as f64) as f32
+    }
+
+    /// Samples an exponential distribution with the given rate (`lambda`), so the
+    /// mean of the samples is `1.0 / lambda`.
+    #[inline]
+    pub fn exponential_f64(&self, lambda: f64) -> f64 {
+        debug_assert!(lambda > 0.0);
+
+        -self.get_f64_open().ln() / lambda
+    }
+
+    #[inline]
+    pub fn exponential_f32(&self, lambda: f32) -> f32 {
+        self.exponential_f64(lambda as f64) as f32
+    }
+
+    /// Samples a Cauchy distribution. Beware it has no defined mean or variance,
+    /// so expect the occasional huge value.
+    #[inline]
+    pub fn cauchy_f64(&self, median: f64, scale: f64) -> f64 {
+        debug_assert!(scale > 0.0);
+
+        median + scale * (PI * (self.get_f64() - 0.5)).tan()
+    }
+
+    #[inline]
+    pub fn cauchy_f32(&self, median: f32, scale: f32) -> f32 {
+        self.cauchy_f64(median as f64, scale as f64) as f32
+    }
+
+    /// Samples a triangular distribution within `[min, max]`, peaking at `mode`.
+    #[inline]
+    pub fn triangular_f64(&self, min: f64, max: f64, mode: f64) -> f64 {
+        debug_assert!(min < max);
+        debug_assert!((min..=max).contains(&mode));
+
+        let range = max - min;
+        let value = self.get_f64();
+
+        if value < (mode - min) / range {
+            min + (value * range * (mode - min)).sqrt()
+        } else {
+            max - ((1.0 - value) * range * (max - mode)).sqrt()
+        }
+    }
+
+    #[inline]
+    pub fn triangular_f32(&self, min: f32, max: f32, mode: f32) -> f32 {
+        self.triangular_f64(min as f64, max as f64, mode as f64) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PetriRand;
+
+    const SAMPLES: usize = 100_000;
+
+    fn mean_and_variance(samples: impl Iterator<Item = f64>) -> (f64, f64) {
+        let samples: Vec<f64> = samples.take(SAMPLES).collect();
+        let mean = samples.iter().sum::<f64>() / SAMPLES as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / SAMPLES as f64;
+
+        (mean, variance)
+    }
+
+    #[test]
+    fn normal() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let (mean, variance) =
+            mean_and_variance(std::iter::repeat_with(|| rng.normal_f64(5.0, 2.0)));
+
+        assert!((mean - 5.0).abs() < 0.05, "mean was {}", mean);
+        assert!((variance - 4.0).abs() < 0.1, "variance was {}", variance);
+    }
+
+    #[test]
+    fn exponential() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let (mean, variance) =
+            mean_and_variance(std::iter::repeat_with(|| rng.exponential_f64(2.0)));
+
+        assert!((mean - 0.5).abs() < 0.01, "mean was {}", mean);
+        assert!((variance - 0.25).abs() < 0.01, "variance was {}", variance);
+    }
+
+    #[test]
+    fn cauchy() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        // No mean to check against, but half the samples fall within `median ± scale`
+        let within = std::iter::repeat_with(|| rng.cauchy_f64(1.0, 0.5))
+            .take(SAMPLES)
+            .filter(|x| (0.5..=1.5).contains(x))
+            .count();
+
+        assert!((within as f64 / SAMPLES as f64 - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn triangular() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let samples: Vec<f32> = std::iter::repeat_with(|| rng.triangular_f32(-1.0, 2.0, 0.0))
+            .take(SAMPLES)
+            .collect();
+
+        assert!(samples.iter().all(|x| (-1.0..=2.0).contains(x)));
+
+        let (mean, _) = mean_and_variance(samples.into_iter().map(f64::from));
+
+        // Mean of a triangular distribution is (min + max + mode) / 3
+        assert!((mean - 1.0 / 3.0).abs() < 0.01, "mean was {}", mean);
+    }
+}
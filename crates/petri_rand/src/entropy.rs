@@ -1,19 +1,171 @@
+//! Sources of seeds for new generators.
+//!
+//! Seeds are only ever derived with SplitMix64 mixing defined in this crate, so
+//! a given input always yields the same seed, regardless of Rust version.
+
 use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-    thread,
+    env,
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 #[cfg(target_arch = "wasm32")]
-use instant::Instant;
+use instant::SystemTime;
 #[cfg(not(target_arch = "wasm32"))]
-use std::time::Instant;
+use std::time::SystemTime;
+
+use crate::gen::mix;
+
+/// Name of the environment variable read by [`EnvSeed`].
+pub const SEED_VAR: &str = "PETRI_SEED";
+
+pub trait EntropySource {
+    fn seed(&self) -> u64;
+}
+
+/// Always yields the same seed. Useful for tests, or to replay a known run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FixedSeed(pub u64);
+
+impl EntropySource for FixedSeed {
+    #[inline]
+    fn seed(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Weak but dependency free entropy, mixing the system clock, a stack address
+/// (varying per thread and with ASLR) and a call counter.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeEntropy;
+
+impl EntropySource for TimeEntropy {
+    #[inline]
+    fn seed(&self) -> u64 {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or_default();
+        let local = 0u8;
+        let address = &local as *const u8 as u64;
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+        mix(mix(nanos ^ address.rotate_left(32)) ^ count)
+    }
+}
+
+/// Randomness from the operating system, via `getrandom`. Falls back to
+/// [`TimeEntropy`] on the rare platforms where that fails.
+#[cfg(feature = "getrandom")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsEntropy;
+
+#[cfg(feature = "getrandom")]
+impl EntropySource for OsEntropy {
+    #[inline]
+    fn seed(&self) -> u64 {
+        let mut bytes = [0u8; core::mem::size_of::<u64>()];
+
+        match getrandom::getrandom(&mut bytes) {
+            Ok(()) => u64::from_le_bytes(bytes),
+            Err(_) => TimeEntropy.seed(),
+        }
+    }
+}
+
+/// The best available source of fresh entropy: OS randomness when the
+/// `getrandom` feature is enabled, the system clock otherwise.
+#[cfg(feature = "getrandom")]
+pub type SystemEntropy = OsEntropy;
+
+/// The best available source of fresh entropy: OS randomness when the
+/// `getrandom` feature is enabled, the system clock otherwise.
+#[cfg(not(feature = "getrandom"))]
+pub type SystemEntropy = TimeEntropy;
+
+/// Reads the seed from an environment variable ([`SEED_VAR`] by default), so a
+/// run can be replayed without code changes, and uses `fallback` when unset.
+///
+/// Panics if the variable is set, but not to a valid `u64`.
+#[derive(Debug, Clone, Copy)]
+pub struct EnvSeed<S: EntropySource> {
+    var: &'static str,
+    fallback: S,
+}
+
+impl<S: EntropySource> EnvSeed<S> {
+    pub fn new(fallback: S) -> Self {
+        Self::with_var(SEED_VAR, fallback)
+    }
+
+    pub fn with_var(var: &'static str, fallback: S) -> Self {
+        Self { var, fallback }
+    }
+}
+
+impl<S: EntropySource + Default> Default for EnvSeed<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S: EntropySource> EntropySource for EnvSeed<S> {
+    fn seed(&self) -> u64 {
+        match env::var(self.var) {
+            Ok(seed) => seed.trim().parse().unwrap_or_else(|_| {
+                panic!(
+                    "{} must be an unsigned 64-bit integer, found {:?}",
+                    self.var, seed
+                )
+            }),
+            Err(_) => self.fallback.seed(),
+        }
+    }
+}
 
+/// [`SystemEntropy`], unless overridden by the `PETRI_SEED` environment variable.
+pub type DefaultEntropy = EnvSeed<SystemEntropy>;
+
+/// Seeds the thread local generators. These deliberately ignore `PETRI_SEED`,
+/// as every thread would otherwise end up with an identical stream.
 #[inline]
-pub fn generate_entropy() -> u64 {
-    let mut hasher = DefaultHasher::new();
-    Instant::now().hash(&mut hasher);
-    thread::current().id().hash(&mut hasher);
-    let hash = hasher.finish();
-    (hash << 1) | 1
+pub(crate) fn generate_entropy() -> u64 {
+    SystemEntropy::default().seed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_seed() {
+        assert_eq!(FixedSeed(42).seed(), 42);
+    }
+
+    #[test]
+    fn time_entropy_varies() {
+        assert_ne!(TimeEntropy.seed(), TimeEntropy.seed());
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn os_entropy_varies() {
+        assert_ne!(OsEntropy.seed(), OsEntropy.seed());
+    }
+
+    #[test]
+    fn env_seed() {
+        const VAR: &str = "PETRI_SEED_ENTROPY_TEST";
+
+        let source = EnvSeed::with_var(VAR, FixedSeed(7));
+
+        assert_eq!(source.seed(), 7);
+
+        env::set_var(VAR, " 1234 ");
+
+        assert_eq!(source.seed(), 1234);
+
+        env::remove_var(VAR);
+    }
 }
@@ -0,0 +1,145 @@
+use std::ops::Range;
+
+use crate::{gen::OUTPUT_SIZE, Generator, PetriRand};
+
+/// Scale for turning the top 24 bits of a `u32` into an `f32` in `[0.0, 1.0)`.
+const F32_SCALE: f32 = 1.0 / (1u32 << 24) as f32;
+
+impl<R: Generator> PetriRand<R> {
+    /// Fills the whole buffer with random bytes.
+    #[inline]
+    pub fn fill_bytes(&self, bytes: &mut [u8]) {
+        let mut chunks = bytes.chunks_exact_mut(OUTPUT_SIZE);
+
+        for chunk in &mut chunks {
+            chunk.copy_from_slice(&self.rng.rand());
+        }
+
+        let remainder = chunks.into_remainder();
+
+        if !remainder.is_empty() {
+            let random = self.rng.rand();
+            remainder.copy_from_slice(&random[..remainder.len()]);
+        }
+    }
+
+    /// Fills the buffer with random `u32`s, using both halves of every 64-bit
+    /// output, so it takes half the generator steps of calling `get_u32` per value.
+    #[inline]
+    pub fn fill_u32(&self, values: &mut [u32]) {
+        let mut chunks = values.chunks_exact_mut(2);
+
+        for chunk in &mut chunks {
+            let random = self.get_u64();
+            chunk[0] = random as u32;
+            chunk[1] = (random >> 32) as u32;
+        }
+
+        if let [last] = chunks.into_remainder() {
+            *last = self.get_u32();
+        }
+    }
+
+    /// Fills the buffer with uniform values in `[0.0, 1.0)`, two per generator step.
+    #[inline]
+    pub fn fill_f32(&self, values: &mut [f32]) {
+        self.fill_f32_range(values, 0.0..1.0);
+    }
+
+    /// Fills the buffer with uniform values in `range`, two per generator step.
+    #[inline]
+    pub fn fill_f32_range(&self, values: &mut [f32], range: Range<f32>) {
+        assert!(range.start < range.end, "Empty range");
+
+        let lower = range.start;
+        let scale = (range.end - range.start) * F32_SCALE;
+        // Rounding can land on the excluded end, so clamp to the float just below it
+        let upper = previous_f32(range.end);
+
+        let to_range = |bits: u32| (lower + (bits >> 8) as f32 * scale).min(upper);
+
+        let mut chunks = values.chunks_exact_mut(2);
+
+        for chunk in &mut chunks {
+            let random = self.get_u64();
+            chunk[0] = to_range(random as u32);
+            chunk[1] = to_range((random >> 32) as u32);
+        }
+
+        if let [last] = chunks.into_remainder() {
+            *last = to_range(self.get_u32());
+        }
+    }
+}
+
+/// The largest `f32` that is smaller than `value`.
+#[inline]
+fn previous_f32(value: f32) -> f32 {
+    debug_assert!(value.is_finite());
+
+    let bits = value.to_bits();
+
+    if value > 0.0 {
+        f32::from_bits(bits - 1)
+    } else if value < 0.0 {
+        f32::from_bits(bits + 1)
+    } else {
+        // Smallest negative subnormal
+        -f32::from_bits(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PetriRand;
+
+    #[test]
+    fn fill_bytes() {
+        let rng = PetriRand::with_seed(Default::default());
+        let reference = PetriRand::with_seed(Default::default());
+
+        let mut bytes = [0u8; 11];
+        rng.fill_bytes(&mut bytes);
+
+        let expected: Vec<u8> = reference
+            .get_u64()
+            .to_le_bytes()
+            .into_iter()
+            .chain(reference.get_u64().to_le_bytes().into_iter().take(3))
+            .collect();
+
+        assert_eq!(bytes.as_ref(), expected.as_slice());
+    }
+
+    #[test]
+    fn fill_u32() {
+        let rng = PetriRand::with_seed(Default::default());
+        let reference = PetriRand::with_seed(Default::default());
+
+        let mut values = [0u32; 3];
+        rng.fill_u32(&mut values);
+
+        let first = reference.get_u64();
+
+        assert_eq!(
+            values,
+            [first as u32, (first >> 32) as u32, reference.get_u32()]
+        );
+    }
+
+    #[test]
+    fn fill_f32_range() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let mut values = vec![0.0f32; 10_001];
+        rng.fill_f32_range(&mut values, -1.0..1.0);
+
+        assert!(values.iter().all(|value| (-1.0..1.0).contains(value)));
+        assert_eq!(super::previous_f32(1.0), 0.99999994);
+        assert_eq!(super::previous_f32(-1.0), -1.0000001);
+
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+
+        assert!(mean.abs() < 0.02, "mean was {}", mean);
+    }
+}
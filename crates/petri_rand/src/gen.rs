@@ -1,44 +1,56 @@
-use std::cell::Cell;
+pub(crate) mod wyrand;
+mod xoshiro;
 
-#[derive(Debug)]
-#[repr(transparent)]
-pub struct WyRand {
-    state: Cell<u64>,
-}
+pub use self::{wyrand::WyRand, xoshiro::Xoshiro256PlusPlus};
+
+/// Size in bytes of a single generator output.
+pub const OUTPUT_SIZE: usize = core::mem::size_of::<u64>();
+
+/// Core trait for the PRNG algorithms that can back a [`crate::PetriRand`].
+///
+/// Generators use interior mutability, so that a single instance can be shared
+/// by reference the same way `PetriRand` is.
+pub trait Generator: Clone + Default {
+    /// The full internal state, as exported by [`Generator::state`].
+    type State: Copy;
+
+    fn with_seed(seed: u64) -> Self;
+
+    fn reseed(&self, seed: u64);
 
-impl WyRand {
-    #[inline]
-    pub fn with_seed(seed: u64) -> Self {
-        Self {
-            state: Cell::new(seed << 1 | 1),
-        }
-    }
-
-    #[inline]
-    pub fn reseed(&self, seed: u64) {
-        self.state.set(seed << 1 | 1);
-    }
-
-    #[inline]
-    pub fn rand(&self) -> [u8; core::mem::size_of::<u64>()] {
-        let state = self.state.get().wrapping_add(0xa0761d6478bd642f);
-        self.state.set(state);
-        let t: u128 = (state as u128).wrapping_mul((state ^ 0xe7037ed1a0b428db) as u128);
-        let ret = (t.wrapping_shr(64) ^ t) as u64;
-        ret.to_le_bytes()
-    }
+    /// Restores a generator from a previously exported [`Generator::state`].
+    /// Unlike [`Generator::with_seed`], the state is used as-is.
+    fn from_state(state: Self::State) -> Self;
+
+    /// Exports the exact internal state, so the stream can be resumed later
+    /// with [`Generator::from_state`] or [`Generator::set_state`].
+    fn state(&self) -> Self::State;
+
+    fn set_state(&self, state: Self::State);
+
+    /// Advances the generator, returning 64 bits of output as little endian bytes.
+    fn rand(&self) -> [u8; OUTPUT_SIZE];
+
+    /// Derives an independent generator for the given stream id. This is a pure
+    /// function of the current state and `stream`, and does not advance `self`.
+    fn fork(&self, stream: u64) -> Self;
 }
 
-impl Clone for WyRand {
-    fn clone(&self) -> Self {
-        Self {
-            state: Cell::new(u64::from_le_bytes(self.rand())),
-        }
-    }
+/// SplitMix64 finaliser, used to decorrelate seeds and stream ids.
+#[inline]
+pub(crate) fn mix(value: u64) -> u64 {
+    let mut z = value;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
 }
 
-impl Default for WyRand {
-    fn default() -> Self {
-        Self::with_seed(Default::default())
-    }
+/// Combines a generator state with a stream id into a new, well mixed seed.
+#[inline]
+pub(crate) fn mix_stream(state: u64, stream: u64) -> u64 {
+    mix(state ^ mix(stream.wrapping_add(0x9e3779b97f4a7c15)))
 }
+
+/// The generator used by `PetriRand` when none is specified. For another, name it
+/// as in `PetriRand<Xoshiro256PlusPlus>` or use `PetriRand::from_generator`.
+pub type DefaultGenerator = WyRand;
@@ -0,0 +1,83 @@
+use std::cell::Cell;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{mix_stream, Generator, OUTPUT_SIZE};
+
+/// WyRand's state is a Weyl sequence, advanced by this constant on every step.
+pub(crate) const INCREMENT: u64 = 0xa0761d6478bd642f;
+
+/// Hashes a WyRand state into its output.
+#[inline]
+pub(crate) fn output(state: u64) -> u64 {
+    let t: u128 = (state as u128).wrapping_mul((state ^ 0xe7037ed1a0b428db) as u128);
+    (t.wrapping_shr(64) ^ t) as u64
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[repr(transparent)]
+pub struct WyRand {
+    state: Cell<u64>,
+}
+
+impl Generator for WyRand {
+    type State = u64;
+
+    #[inline]
+    fn with_seed(seed: u64) -> Self {
+        Self {
+            state: Cell::new(seed << 1 | 1),
+        }
+    }
+
+    #[inline]
+    fn reseed(&self, seed: u64) {
+        self.state.set(seed << 1 | 1);
+    }
+
+    #[inline]
+    fn from_state(state: u64) -> Self {
+        Self {
+            state: Cell::new(state),
+        }
+    }
+
+    #[inline]
+    fn state(&self) -> u64 {
+        self.state.get()
+    }
+
+    #[inline]
+    fn set_state(&self, state: u64) {
+        self.state.set(state);
+    }
+
+    #[inline]
+    fn rand(&self) -> [u8; OUTPUT_SIZE] {
+        let state = self.state.get().wrapping_add(INCREMENT);
+        self.state.set(state);
+        output(state).to_le_bytes()
+    }
+
+    #[inline]
+    fn fork(&self, stream: u64) -> Self {
+        Self::from_state(mix_stream(self.state.get(), stream) | 1)
+    }
+}
+
+impl Clone for WyRand {
+    fn clone(&self) -> Self {
+        Self {
+            state: Cell::new(u64::from_le_bytes(self.rand())),
+        }
+    }
+}
+
+impl Default for WyRand {
+    fn default() -> Self {
+        Self::with_seed(Default::default())
+    }
+}
@@ -1,12 +1,34 @@
-use std::{
-    ops::{Bound, RangeBounds},
-    rc::Rc,
+use std::{ops::RangeBounds, rc::Rc};
+
+use crate::entropy::generate_entropy;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::{
+    entropy::{
+        DefaultEntropy, EntropySource, EnvSeed, FixedSeed, SystemEntropy, TimeEntropy, SEED_VAR,
+    },
+    gen::{DefaultGenerator, Generator, WyRand, Xoshiro256PlusPlus},
+    quasi::{radical_inverse, Halton, PoissonDisk, R2},
+    sync::SyncPetriRand,
+    weighted::WeightedIndex,
 };
 
-use crate::{entropy::generate_entropy, gen::WyRand};
+#[cfg(feature = "getrandom")]
+pub use crate::entropy::OsEntropy;
 
+#[cfg(feature = "rand_core")]
+mod compat;
+mod distributions;
 mod entropy;
+mod fill;
 mod gen;
+mod quasi;
+mod range;
+mod seq;
+mod sync;
+mod weighted;
 
 const SCALE: f64 = 2.0 * (1u64 << 63) as f64;
 
@@ -15,64 +37,86 @@ thread_local! {
 }
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[repr(transparent)]
-pub struct PetriRand {
-    rng: WyRand,
-}
-
-macro_rules! index {
-    ($bigger:tt) => {
-        #[inline]
-        pub fn index(&self, bounds: impl RangeBounds<usize>) -> usize {
-            const BITS: $bigger = core::mem::size_of::<usize>() as $bigger * 8;
-            let lower = match bounds.start_bound() {
-                Bound::Included(lower) => *lower,
-                Bound::Excluded(lower) => lower.saturating_add(1),
-                Bound::Unbounded => usize::MIN,
-            };
-            let upper = match bounds.end_bound() {
-                Bound::Included(upper) => upper.saturating_sub(lower).saturating_add(1),
-                Bound::Excluded(upper) => upper.saturating_sub(lower),
-                Bound::Unbounded => usize::MAX,
-            };
-
-            let mut value = usize::from_le_bytes(self.rng.rand());
-            let mut m = (upper as $bigger).wrapping_mul(value as $bigger);
-            if (m as usize) < upper {
-                let t = (!upper + 1) % upper;
-                while (m as usize) < t {
-                    value = usize::from_le_bytes(self.rng.rand());
-                    m = (upper as $bigger).wrapping_mul(value as $bigger);
-                }
-            }
-            (m >> BITS) as usize + lower
-        }
-    };
+pub struct PetriRand<R: Generator = DefaultGenerator> {
+    rng: R,
 }
 
 impl PetriRand {
     #[inline]
     pub fn new() -> Self {
         Self {
-            rng: WyRand::with_seed(PETRI.with(|t| t.get_u64())),
+            rng: DefaultGenerator::with_seed(PETRI.with(|t| t.get_u64())),
         }
     }
 
     #[inline]
     pub fn with_seed(seed: u64) -> Self {
         Self {
-            rng: WyRand::with_seed(seed),
+            rng: DefaultGenerator::with_seed(seed),
         }
     }
 
+    #[inline]
+    pub fn reseed_local(seed: u64) {
+        PETRI.with(|t| t.reseed(seed));
+    }
+}
+
+impl<R: Generator> PetriRand<R> {
+    /// Wraps an already seeded generator, for when a different algorithm than
+    /// [`DefaultGenerator`] is wanted.
+    #[inline]
+    pub fn from_generator(rng: R) -> Self {
+        Self { rng }
+    }
+
+    #[inline]
+    pub fn generator(&self) -> &R {
+        &self.rng
+    }
+
     #[inline]
     pub fn reseed(&self, seed: u64) {
         self.rng.reseed(seed);
     }
 
+    /// Restores a generator from a state exported with [`PetriRand::state`],
+    /// resuming the exact same sequence of draws.
     #[inline]
-    pub fn reseed_local(seed: u64) {
-        PETRI.with(|t| t.reseed(seed));
+    pub fn from_state(state: R::State) -> Self {
+        Self {
+            rng: R::from_state(state),
+        }
+    }
+
+    #[inline]
+    pub fn state(&self) -> R::State {
+        self.rng.state()
+    }
+
+    #[inline]
+    pub fn set_state(&self, state: R::State) {
+        self.rng.set_state(state);
+    }
+
+    /// Derives an independent generator for `stream`, such as an entity or thread
+    /// index. The result only depends on the current state and `stream`, and the
+    /// parent is not advanced, so forked generators are reproducible regardless of
+    /// the order in which they are created or used.
+    #[inline]
+    pub fn fork(&self, stream: u64) -> Self {
+        Self {
+            rng: self.rng.fork(stream),
+        }
+    }
+
+    /// Forks `n` generators, for streams `0..n`. See [`PetriRand::fork`].
+    #[inline]
+    pub fn split_n(&self, n: usize) -> Vec<Self> {
+        (0..n as u64).map(|stream| self.fork(stream)).collect()
     }
 
     #[inline]
@@ -89,9 +133,18 @@ impl PetriRand {
         u32::from_le_bytes(bytes)
     }
 
+    /// Returns a uniform value in the range `[0.0, 1.0)`, using all 24 bits of
+    /// mantissa precision.
     #[inline]
     pub fn get_f32(&self) -> f32 {
-        (self.get_u32() as f32) / (u32::MAX as f32)
+        (self.get_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    /// Returns a uniform value in the range `[0.0, 1.0)`, using the full 53 bits of
+    /// mantissa precision.
+    #[inline]
+    pub fn get_f64(&self) -> f64 {
+        (self.get_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
     }
 
     #[inline]
@@ -104,12 +157,13 @@ impl PetriRand {
         self.rng.rand()[0] % 2 == 0
     }
 
-    #[cfg(target_pointer_width = "16")]
-    index!(u32);
-    #[cfg(target_pointer_width = "32")]
-    index!(u64);
-    #[cfg(target_pointer_width = "64")]
-    index!(u128);
+    /// Samples an index within `bounds`, the same as `range_usize`.
+    ///
+    /// Panics if the range is empty.
+    #[inline]
+    pub fn index(&self, bounds: impl RangeBounds<usize>) -> usize {
+        self.range_usize(bounds)
+    }
 
     #[inline]
     pub fn chance(&self, rate: f64) -> bool {
@@ -134,3 +188,73 @@ impl PetriRand {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_round_trip() {
+        let rng = PetriRand::with_seed(Default::default());
+        rng.get_u64();
+
+        let checkpoint: PetriRand = PetriRand::from_state(rng.state());
+
+        for _ in 0..10 {
+            assert_eq!(rng.get_u64(), checkpoint.get_u64());
+        }
+
+        rng.set_state(checkpoint.state());
+
+        assert_eq!(rng.get_u64(), checkpoint.get_u64());
+    }
+
+    #[test]
+    fn fork() {
+        let rng = PetriRand::with_seed(Default::default());
+        let state = rng.state();
+
+        let streams = rng.split_n(4);
+
+        // Forking leaves the parent untouched
+        assert_eq!(rng.state(), state);
+
+        // Forking in any order yields the same streams
+        for (stream, forked) in streams.iter().enumerate().rev() {
+            assert_eq!(rng.fork(stream as u64).state(), forked.state());
+        }
+
+        let firsts: Vec<u64> = streams.iter().map(|rng| rng.get_u64()).collect();
+
+        for (i, a) in firsts.iter().enumerate() {
+            for b in &firsts[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn alternative_generator() {
+        let rng = PetriRand::from_generator(Xoshiro256PlusPlus::with_seed(Default::default()));
+        let checkpoint = PetriRand::<Xoshiro256PlusPlus>::from_state(rng.state());
+
+        assert_ne!(rng.get_u64(), PetriRand::<WyRand>::default().get_u64());
+        assert_eq!(
+            checkpoint.get_u64(),
+            PetriRand::<Xoshiro256PlusPlus>::default().get_u64()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let rng = PetriRand::with_seed(Default::default());
+        rng.get_u64();
+
+        let serialized = serde_json::to_string(&rng).unwrap();
+        let restored: PetriRand = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(serialized, rng.state().to_string());
+        assert_eq!(rng.get_u64(), restored.get_u64());
+    }
+}
@@ -0,0 +1,248 @@
+//! Low-discrepancy sequences and blue noise sampling, for spreading points
+//! evenly rather than independently at random, which tends to clump.
+
+use crate::{Generator, PetriRand};
+
+const PRIMES: [u64; 16] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53];
+
+/// Van der Corput radical inverse: mirrors the digits of `index` in `base`
+/// around the decimal point.
+#[inline]
+pub fn radical_inverse(mut index: u64, base: u64) -> f64 {
+    debug_assert!(base > 1);
+
+    let inverse_base = 1.0 / base as f64;
+    let mut fraction = inverse_base;
+    let mut result = 0.0;
+
+    while index > 0 {
+        result += (index % base) as f64 * fraction;
+        index /= base;
+        fraction *= inverse_base;
+    }
+
+    result
+}
+
+/// Halton sequence over `D` dimensions (up to 16), using the first `D` primes as
+/// bases. Yields points in `[0.0, 1.0)^D`.
+#[derive(Debug, Clone)]
+pub struct Halton<const D: usize> {
+    index: u64,
+    offset: [f64; D],
+}
+
+impl<const D: usize> Halton<D> {
+    /// Starts the sequence, with every point shifted by `offset` and wrapped back
+    /// into the unit cube (a Cranley-Patterson rotation). Use a zero offset for the
+    /// canonical sequence, or a random one to get a different but still evenly
+    /// spread set of points.
+    pub fn new(offset: [f64; D]) -> Self {
+        assert!(D > 0 && D <= PRIMES.len(), "Unsupported dimensions");
+
+        // Index 0 is the origin in every base, so skip it
+        Self { index: 1, offset }
+    }
+}
+
+impl<const D: usize> Iterator for Halton<D> {
+    type Item = [f64; D];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut point = [0.0; D];
+
+        for ((value, base), offset) in point.iter_mut().zip(PRIMES).zip(self.offset) {
+            *value = (radical_inverse(self.index, base) + offset).fract();
+        }
+
+        self.index += 1;
+
+        Some(point)
+    }
+}
+
+/// Roberts' R2 sequence, based on the plastic number. Cheaper than Halton, and
+/// with an even spread at any number of points.
+#[derive(Debug, Clone)]
+pub struct R2 {
+    point: [f64; 2],
+}
+
+impl R2 {
+    /// The plastic number, the unique real root of `x^3 = x + 1`.
+    const PLASTIC: f64 = 1.324_717_957_244_746;
+    const STEP: [f64; 2] = [1.0 / Self::PLASTIC, 1.0 / (Self::PLASTIC * Self::PLASTIC)];
+
+    /// Starts the sequence at `offset`, which should be within `[0.0, 1.0)`.
+    /// Different offsets yield different but equally evenly spread points.
+    pub fn new(offset: [f64; 2]) -> Self {
+        Self { point: offset }
+    }
+}
+
+impl Iterator for R2 {
+    type Item = [f64; 2];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let point = self.point;
+
+        for (value, step) in self.point.iter_mut().zip(Self::STEP) {
+            *value = (*value + step).fract();
+        }
+
+        Some(point)
+    }
+}
+
+/// Poisson-disk sampling over a rectangle with Bridson's algorithm. Points are
+/// random, but never closer than `radius` to each other, and get added until
+/// no more fit.
+#[derive(Debug, Clone)]
+pub struct PoissonDisk {
+    width: f32,
+    height: f32,
+    radius: f32,
+}
+
+impl PoissonDisk {
+    /// Candidates tried around each point before giving up on it.
+    const ATTEMPTS: usize = 30;
+
+    pub fn new(width: f32, height: f32, radius: f32) -> Self {
+        assert!(width > 0.0 && height > 0.0, "Invalid area");
+        assert!(radius > 0.0, "Invalid radius");
+
+        Self {
+            width,
+            height,
+            radius,
+        }
+    }
+
+    /// Samples points within `[0.0, width) x [0.0, height)`.
+    pub fn sample<R: Generator>(&self, rng: &PetriRand<R>) -> Vec<[f32; 2]> {
+        // Cells small enough to each hold at most one point
+        let cell = self.radius / std::f32::consts::SQRT_2;
+        let columns = (self.width / cell).ceil() as usize;
+        let rows = (self.height / cell).ceil() as usize;
+
+        let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+        let mut points: Vec<[f32; 2]> = Vec::new();
+        let mut active: Vec<usize> = Vec::new();
+
+        let grid_index = |[x, y]: [f32; 2]| {
+            let column = ((x / cell) as usize).min(columns - 1);
+            let row = ((y / cell) as usize).min(rows - 1);
+            (column, row)
+        };
+
+        let first = [
+            rng.range_f32(0.0..self.width),
+            rng.range_f32(0.0..self.height),
+        ];
+        let (column, row) = grid_index(first);
+        grid[row * columns + column] = Some(0);
+        points.push(first);
+        active.push(0);
+
+        while !active.is_empty() {
+            let slot = rng.index(..active.len());
+            let [x, y] = points[active[slot]];
+
+            let candidate = (0..Self::ATTEMPTS).find_map(|_| {
+                // Uniform over the annulus between `radius` and `2 * radius`
+                let angle = rng.range_f32(0.0..std::f32::consts::TAU);
+                let distance = self.radius * (rng.range_f32(1.0..4.0)).sqrt();
+                let candidate = [x + distance * angle.cos(), y + distance * angle.sin()];
+
+                if !(0.0..self.width).contains(&candidate[0])
+                    || !(0.0..self.height).contains(&candidate[1])
+                {
+                    return None;
+                }
+
+                let (column, row) = grid_index(candidate);
+
+                let rows_near = row.saturating_sub(2)..(row + 3).min(rows);
+                let too_close = rows_near.into_iter().any(|row| {
+                    let columns_near = column.saturating_sub(2)..(column + 3).min(columns);
+                    columns_near.into_iter().any(|column| {
+                        grid[row * columns + column].into_iter().any(|neighbour| {
+                            let [nx, ny] = points[neighbour];
+                            let (dx, dy) = (nx - candidate[0], ny - candidate[1]);
+                            dx * dx + dy * dy < self.radius * self.radius
+                        })
+                    })
+                });
+
+                (!too_close).then_some((candidate, column, row))
+            });
+
+            match candidate {
+                Some((candidate, column, row)) => {
+                    grid[row * columns + column] = Some(points.len());
+                    active.push(points.len());
+                    points.push(candidate);
+                }
+                None => {
+                    active.swap_remove(slot);
+                }
+            }
+        }
+
+        points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn halton() {
+        let points: Vec<[f64; 2]> = Halton::new([0.0; 2]).take(3).collect();
+
+        assert_relative_eq!(points[0].as_ref(), [0.5, 1.0 / 3.0].as_ref());
+        assert_relative_eq!(points[1].as_ref(), [0.25, 2.0 / 3.0].as_ref());
+        assert_relative_eq!(points[2].as_ref(), [0.75, 1.0 / 9.0].as_ref());
+
+        let rotated = Halton::new([0.75]).next().unwrap();
+
+        assert_relative_eq!(rotated[0], 0.25);
+    }
+
+    #[test]
+    fn r2_is_evenly_spread() {
+        // Every cell of a 10x10 grid gets at least one of 200 points, which
+        // independent uniform points would be very unlikely to manage.
+        let mut cells = [0usize; 100];
+
+        for [x, y] in R2::new([0.5, 0.5]).take(200) {
+            assert!((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y));
+            cells[(y * 10.0) as usize * 10 + (x * 10.0) as usize] += 1;
+        }
+
+        assert!(cells.iter().all(|&count| count > 0));
+    }
+
+    #[test]
+    fn poisson_disk() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let points = PoissonDisk::new(100.0, 50.0, 5.0).sample(&rng);
+
+        // A maximal packing at this radius holds well over a hundred points
+        assert!(points.len() > 100, "only {} points", points.len());
+
+        for (i, [x, y]) in points.iter().enumerate() {
+            assert!((0.0..100.0).contains(x) && (0.0..50.0).contains(y));
+
+            for [ox, oy] in &points[i + 1..] {
+                assert!(((x - ox).powi(2) + (y - oy).powi(2)).sqrt() >= 5.0);
+            }
+        }
+    }
+}
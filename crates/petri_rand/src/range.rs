@@ -0,0 +1,253 @@
+use std::ops::{Bound, RangeBounds};
+
+use crate::{Generator, PetriRand};
+
+/// Unbiased sampling in `[0, span)` using Lemire's multiply + reject method.
+macro_rules! bounded {
+    ($name:ident, $ty:ty, $bigger:ty, $gen:ident) => {
+        #[inline]
+        fn $name(&self, span: $ty) -> $ty {
+            let mut m = (self.$gen() as $ty as $bigger).wrapping_mul(span as $bigger);
+            if (m as $ty) < span {
+                let t = span.wrapping_neg() % span;
+                while (m as $ty) < t {
+                    m = (self.$gen() as $ty as $bigger).wrapping_mul(span as $bigger);
+                }
+            }
+            (m >> <$ty>::BITS) as $ty
+        }
+    };
+}
+
+macro_rules! range_integer {
+    ($name:ident, $ty:ty, $unsigned:ty, $bounded:ident, $gen:ident) => {
+        /// Samples uniformly within `bounds`, which may be inclusive or exclusive
+        /// on either end. Unbounded ends extend to the limits of the type.
+        ///
+        /// Panics if the range is empty.
+        #[inline]
+        pub fn $name(&self, bounds: impl RangeBounds<$ty>) -> $ty {
+            let lower = match bounds.start_bound() {
+                Bound::Included(lower) => *lower,
+                Bound::Excluded(lower) => lower.checked_add(1).expect("Empty range"),
+                Bound::Unbounded => <$ty>::MIN,
+            };
+            let upper = match bounds.end_bound() {
+                Bound::Included(upper) => *upper,
+                Bound::Excluded(upper) => upper.checked_sub(1).expect("Empty range"),
+                Bound::Unbounded => <$ty>::MAX,
+            };
+
+            assert!(lower <= upper, "Empty range");
+
+            let span = (upper as $unsigned)
+                .wrapping_sub(lower as $unsigned)
+                .wrapping_add(1);
+
+            // A span of zero means the range wrapped around, and covers the whole type
+            if span == 0 {
+                return self.$gen() as $ty;
+            }
+
+            (lower as $unsigned).wrapping_add(self.$bounded(span)) as $ty
+        }
+    };
+}
+
+macro_rules! range_float {
+    ($name:ident, $ty:ty, $exclusive:ident, $inclusive:ident) => {
+        /// Samples uniformly within `bounds`. Both ends must be bounded, and
+        /// `Excluded` ends are guaranteed never to be returned.
+        ///
+        /// Panics if the range is empty or unbounded.
+        #[inline]
+        pub fn $name(&self, bounds: impl RangeBounds<$ty>) -> $ty {
+            let (lower, lower_inclusive) = match bounds.start_bound() {
+                Bound::Included(lower) => (*lower, true),
+                Bound::Excluded(lower) => (*lower, false),
+                Bound::Unbounded => panic!("Float ranges must be bounded"),
+            };
+            let (upper, upper_inclusive) = match bounds.end_bound() {
+                Bound::Included(upper) => (*upper, true),
+                Bound::Excluded(upper) => (*upper, false),
+                Bound::Unbounded => panic!("Float ranges must be bounded"),
+            };
+
+            assert!(
+                lower < upper || (lower == upper && lower_inclusive && upper_inclusive),
+                "Empty range"
+            );
+
+            let scale = upper - lower;
+
+            if upper_inclusive {
+                loop {
+                    let value = lower + scale * self.$inclusive();
+                    if lower_inclusive || value > lower {
+                        return value.min(upper);
+                    }
+                }
+            }
+
+            loop {
+                // Rounding can land `value` on `upper`, so reject those rare cases
+                let value = lower + scale * self.$exclusive();
+                if value < upper && (lower_inclusive || value > lower) {
+                    return value;
+                }
+            }
+        }
+    };
+}
+
+impl<R: Generator> PetriRand<R> {
+    #[inline]
+    pub fn get_u128(&self) -> u128 {
+        (self.get_u64() as u128) << 64 | self.get_u64() as u128
+    }
+
+    #[inline]
+    fn get_usize(&self) -> usize {
+        self.get_u64() as usize
+    }
+
+    /// Uniform value in `[0.0, 1.0]`.
+    #[inline]
+    fn unit_f32_inclusive(&self) -> f32 {
+        (self.get_u32() >> 8) as f32 * (1.0 / ((1u32 << 24) - 1) as f32)
+    }
+
+    #[inline]
+    fn unit_f64_inclusive(&self) -> f64 {
+        (self.get_u64() >> 11) as f64 * (1.0 / ((1u64 << 53) - 1) as f64)
+    }
+
+    bounded!(bounded_u8, u8, u16, get_u32);
+    bounded!(bounded_u16, u16, u32, get_u32);
+    bounded!(bounded_u32, u32, u64, get_u32);
+    bounded!(bounded_u64, u64, u128, get_u64);
+    #[cfg(target_pointer_width = "16")]
+    bounded!(bounded_usize, usize, u32, get_usize);
+    #[cfg(target_pointer_width = "32")]
+    bounded!(bounded_usize, usize, u64, get_usize);
+    #[cfg(target_pointer_width = "64")]
+    bounded!(bounded_usize, usize, u128, get_usize);
+
+    /// There is no wider integer to multiply into for `u128`, so the full
+    /// 256-bit product is assembled from 64-bit halves.
+    #[inline]
+    fn bounded_u128(&self, span: u128) -> u128 {
+        #[inline]
+        fn wide_mul(a: u128, b: u128) -> (u128, u128) {
+            const LOWER: u128 = u64::MAX as u128;
+
+            let (a_hi, a_lo) = (a >> 64, a & LOWER);
+            let (b_hi, b_lo) = (b >> 64, b & LOWER);
+
+            let lo_lo = a_lo * b_lo;
+            let hi_lo = a_hi * b_lo;
+            let lo_hi = a_lo * b_hi;
+            let hi_hi = a_hi * b_hi;
+
+            let middle = (lo_lo >> 64) + (hi_lo & LOWER) + (lo_hi & LOWER);
+
+            let hi = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (middle >> 64);
+            let lo = (middle << 64) | (lo_lo & LOWER);
+
+            (hi, lo)
+        }
+
+        let (mut hi, mut lo) = wide_mul(self.get_u128(), span);
+        if lo < span {
+            let t = span.wrapping_neg() % span;
+            while lo < t {
+                (hi, lo) = wide_mul(self.get_u128(), span);
+            }
+        }
+        hi
+    }
+
+    range_integer!(range_u8, u8, u8, bounded_u8, get_u32);
+    range_integer!(range_u16, u16, u16, bounded_u16, get_u32);
+    range_integer!(range_u32, u32, u32, bounded_u32, get_u32);
+    range_integer!(range_u64, u64, u64, bounded_u64, get_u64);
+    range_integer!(range_u128, u128, u128, bounded_u128, get_u128);
+    range_integer!(range_usize, usize, usize, bounded_usize, get_usize);
+    range_integer!(range_i8, i8, u8, bounded_u8, get_u32);
+    range_integer!(range_i16, i16, u16, bounded_u16, get_u32);
+    range_integer!(range_i32, i32, u32, bounded_u32, get_u32);
+    range_integer!(range_i64, i64, u64, bounded_u64, get_u64);
+    range_integer!(range_i128, i128, u128, bounded_u128, get_u128);
+    range_integer!(range_isize, isize, usize, bounded_usize, get_usize);
+
+    range_float!(range_f32, f32, get_f32, unit_f32_inclusive);
+    range_float!(range_f64, f64, get_f64, unit_f64_inclusive);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PetriRand;
+
+    macro_rules! histogram {
+        ($rng:ident.$method:ident($range:expr), $offset:expr, $buckets:expr) => {{
+            let mut histogram = [0usize; $buckets];
+            for _ in 0..10_000 {
+                let value = $rng.$method($range);
+                histogram[(value as i128 - $offset) as usize] += 1;
+            }
+            histogram
+        }};
+    }
+
+    #[test]
+    fn integer_bounds() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let exclusive = histogram!(rng.range_u8(10..15), 10, 5);
+        let inclusive = histogram!(rng.range_i16(-2..=2), -2, 5);
+        let wide = histogram!(rng.range_i128(-1..4), -1, 5);
+        let large = histogram!(rng.range_u128(u128::MAX - 4..), u128::MAX as i128 - 4, 5);
+
+        // Every bucket hit, roughly evenly
+        for histogram in [exclusive, inclusive, wide] {
+            assert!(histogram.iter().all(|&count| (1800..2200).contains(&count)));
+        }
+
+        assert!(large.iter().all(|&count| count > 0));
+    }
+
+    #[test]
+    fn integer_full_range() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        let values: Vec<i8> = std::iter::repeat_with(|| rng.range_i8(..))
+            .take(10_000)
+            .collect();
+
+        assert!(values.contains(&i8::MIN));
+        assert!(values.contains(&i8::MAX));
+        assert_eq!(rng.range_u64(7..=7), 7);
+        assert_eq!(rng.range_isize(-3..-2), -3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Empty range")]
+    fn integer_empty_range() {
+        PetriRand::with_seed(Default::default()).range_u32(5..5);
+    }
+
+    #[test]
+    fn float_bounds() {
+        let rng = PetriRand::with_seed(Default::default());
+
+        for _ in 0..10_000 {
+            let exclusive = rng.range_f32(-1.0..1.0);
+            let inclusive = rng.range_f64(2.0..=3.0);
+
+            assert!((-1.0..1.0).contains(&exclusive));
+            assert!((2.0..=3.0).contains(&inclusive));
+        }
+
+        assert_eq!(rng.range_f32(1.5..=1.5), 1.5);
+    }
+}
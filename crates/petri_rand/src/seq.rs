@@ -0,0 +1,144 @@
+use crate::{Generator, PetriRand};
+
+impl<R: Generator> PetriRand<R> {
+    /// Shuffles the whole slice in place with the Fisher-Yates algorithm.
+    #[inline]
+    pub fn shuffle<T>(&self, list: &mut [T]) {
+        for i in (1..list.len()).rev() {
+            list.swap(i, self.index(..=i));
+        }
+    }
+
+    /// Moves `amount` randomly chosen elements to the front of the slice, in
+    /// random order, returning them along with the untouched remainder. Only
+    /// `amount` swaps are performed, so this is cheaper than a full shuffle
+    /// when only a few elements are needed.
+    #[inline]
+    pub fn partial_shuffle<'a, T>(
+        &self,
+        list: &'a mut [T],
+        amount: usize,
+    ) -> (&'a mut [T], &'a mut [T]) {
+        let amount = amount.min(list.len());
+
+        for i in 0..amount {
+            list.swap(i, self.index(i..list.len()));
+        }
+
+        list.split_at_mut(amount)
+    }
+
+    /// Picks `amount` distinct elements from the list (sampling without
+    /// replacement). Returns fewer if the list is shorter than `amount`.
+    #[inline]
+    pub fn choose_multiple<'a, T>(&self, list: &'a [T], amount: usize) -> Vec<&'a T> {
+        let mut indices: Vec<usize> = (0..list.len()).collect();
+
+        let (chosen, _) = self.partial_shuffle(&mut indices, amount);
+
+        chosen
+            .iter()
+            // SOUND: Indices were generated from the list's own range
+            .map(|&index| unsafe { list.get_unchecked(index) })
+            .collect()
+    }
+
+    /// Picks an element with a probability proportional to its weight, using a
+    /// linear cumulative search. Returns `None` if the list is empty or no element
+    /// has a positive weight. For repeated sampling from the same weights,
+    /// [`crate::WeightedIndex`] is O(1) per sample instead.
+    #[inline]
+    pub fn choose_weighted<'a, T, F>(&self, list: &'a [T], weight: F) -> Option<&'a T>
+    where
+        F: Fn(&T) -> f32,
+    {
+        let weight = |item: &T| weight(item).max(0.0);
+        let total: f32 = list.iter().map(weight).sum();
+
+        if total <= 0.0 || !total.is_finite() {
+            return None;
+        }
+
+        let mut target = self.range_f32(0.0..total);
+
+        list.iter()
+            .find(|&item| {
+                let item_weight = weight(item);
+                if target < item_weight {
+                    true
+                } else {
+                    target -= item_weight;
+                    false
+                }
+            })
+            // Float rounding can leave `target` just above the last weight
+            .or_else(|| list.iter().rev().find(|&item| weight(item) > 0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PetriRand;
+
+    #[test]
+    fn shuffle() {
+        let rng = PetriRand::with_seed(Default::default());
+        let mut list: Vec<usize> = (0..100).collect();
+
+        rng.shuffle(&mut list);
+
+        assert_ne!(list, (0..100).collect::<Vec<_>>());
+
+        list.sort_unstable();
+
+        assert_eq!(list, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn partial_shuffle() {
+        let rng = PetriRand::with_seed(Default::default());
+        let mut list: Vec<usize> = (0..10).collect();
+
+        let (chosen, rest) = rng.partial_shuffle(&mut list, 3);
+
+        assert_eq!(chosen.len(), 3);
+        assert_eq!(rest.len(), 7);
+
+        let (chosen, rest) = rng.partial_shuffle(&mut list, 20);
+
+        assert_eq!(chosen.len(), 10);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn choose_multiple() {
+        let rng = PetriRand::with_seed(Default::default());
+        let list: Vec<usize> = (0..10).collect();
+
+        let mut chosen = rng.choose_multiple(&list, 5);
+
+        chosen.sort_unstable();
+        chosen.dedup();
+
+        assert_eq!(chosen.len(), 5);
+        assert_eq!(rng.choose_multiple(&list, 11).len(), 10);
+    }
+
+    #[test]
+    fn choose_weighted() {
+        let rng = PetriRand::with_seed(Default::default());
+        // (index, weight)
+        let list = [(0, 0.0), (1, 1.0), (2, 3.0)];
+
+        let mut histogram = [0usize; 3];
+        for _ in 0..10_000 {
+            let (chosen, _) = rng.choose_weighted(&list, |&(_, weight)| weight).unwrap();
+            histogram[*chosen] += 1;
+        }
+
+        assert_eq!(histogram[0], 0);
+        assert!((2300..2700).contains(&histogram[1]));
+        assert_eq!(rng.choose_weighted(&[0.0f32, 0.0], |&weight| weight), None);
+        assert_eq!(rng.choose_weighted(&[] as &[f32], |&weight| weight), None);
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{
+    gen::{
+        mix_stream,
+        wyrand::{output, INCREMENT},
+    },
+    Generator, PetriRand, WyRand,
+};
+
+/// Thread safe variant of a WyRand backed `PetriRand`, so it can be shared by
+/// reference across threads, such as from a Bevy `Res`.
+///
+/// WyRand's state only ever advances by a constant, so drawing is a single
+/// lock-free atomic add. Draws are never duplicated or lost, but how draws from
+/// different threads interleave depends on scheduling. For results that are
+/// reproducible regardless of threads, [`SyncPetriRand::fork`] a generator per
+/// entity or task instead of drawing from the shared one directly.
+#[derive(Debug)]
+pub struct SyncPetriRand {
+    state: AtomicU64,
+}
+
+impl SyncPetriRand {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_seed(PetriRand::new().get_u64())
+    }
+
+    /// Seeds the same stream as a `PetriRand<WyRand>` with the same seed.
+    #[inline]
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_state(WyRand::with_seed(seed).state())
+    }
+
+    #[inline]
+    pub fn from_state(state: u64) -> Self {
+        Self {
+            state: AtomicU64::new(state),
+        }
+    }
+
+    #[inline]
+    pub fn state(&self) -> u64 {
+        self.state.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn set_state(&self, state: u64) {
+        self.state.store(state, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn get_u64(&self) -> u64 {
+        let state = self
+            .state
+            .fetch_add(INCREMENT, Ordering::Relaxed)
+            .wrapping_add(INCREMENT);
+
+        output(state)
+    }
+
+    #[inline]
+    pub fn get_u32(&self) -> u32 {
+        self.get_u64() as u32
+    }
+
+    #[inline]
+    pub fn get_f32(&self) -> f32 {
+        (self.get_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    /// Derives a local generator for `stream`, such as an entity or thread index.
+    /// Like [`PetriRand::fork`], this doesn't advance the shared state, so the
+    /// result is the same no matter which thread asks for it, or when.
+    #[inline]
+    pub fn fork(&self, stream: u64) -> PetriRand<WyRand> {
+        PetriRand::from_state(mix_stream(self.state(), stream) | 1)
+    }
+
+    /// Takes a local generator off the shared stream, advancing it by one step.
+    #[inline]
+    pub fn local(&self) -> PetriRand<WyRand> {
+        PetriRand::from_generator(WyRand::with_seed(self.get_u64()))
+    }
+}
+
+impl Default for SyncPetriRand {
+    fn default() -> Self {
+        Self::with_seed(Default::default())
+    }
+}
+
+impl Clone for SyncPetriRand {
+    fn clone(&self) -> Self {
+        Self::from_state(self.state())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::BTreeSet, thread};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn is_send_and_sync() {
+        assert_send_sync::<SyncPetriRand>();
+    }
+
+    #[test]
+    fn same_stream_as_wyrand() {
+        let shared = SyncPetriRand::with_seed(5);
+        let local = PetriRand::<WyRand>::from_generator(WyRand::with_seed(5));
+
+        for _ in 0..10 {
+            assert_eq!(shared.get_u64(), local.get_u64());
+        }
+
+        assert_eq!(shared.fork(3).state(), local.fork(3).state());
+    }
+
+    #[test]
+    fn shared_across_threads() {
+        let shared = SyncPetriRand::with_seed(Default::default());
+        let local = PetriRand::<WyRand>::from_generator(WyRand::with_seed(Default::default()));
+
+        let drawn: BTreeSet<u64> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| (0..1000).map(|_| shared.get_u64()).collect::<Vec<_>>()))
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        // Regardless of how threads interleaved, the same draws were handed out
+        let expected: BTreeSet<u64> = (0..4000).map(|_| local.get_u64()).collect();
+
+        assert_eq!(drawn, expected);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{Generator, PetriRand};
+
+/// Precomputed alias table (Vose's method) for repeatedly sampling indices in
+/// proportion to a fixed set of weights. Construction is O(n), after which each
+/// sample is O(1).
+#[derive(Debug, Clone)]
+pub struct WeightedIndex {
+    probabilities: Vec<f64>,
+    aliases: Vec<usize>,
+}
+
+impl WeightedIndex {
+    /// Builds the table, returning `None` if there are no weights, any weight is
+    /// negative or not finite, or all weights are zero.
+    pub fn new(weights: impl IntoIterator<Item = f32>) -> Option<Self> {
+        let weights: Vec<f64> = weights.into_iter().map(f64::from).collect();
+
+        if weights
+            .iter()
+            .any(|weight| !weight.is_finite() || *weight < 0.0)
+        {
+            return None;
+        }
+
+        let total: f64 = weights.iter().sum();
+
+        if weights.is_empty() || total <= 0.0 {
+            return None;
+        }
+
+        let len = weights.len();
+        let mut probabilities: Vec<f64> = weights
+            .iter()
+            .map(|weight| weight * len as f64 / total)
+            .collect();
+        let mut aliases: Vec<usize> = (0..len).collect();
+
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..len).partition(|&i| probabilities[i] < 1.0);
+
+        while let (Some(&less), Some(&more)) = (small.last(), large.last()) {
+            small.pop();
+
+            aliases[less] = more;
+            probabilities[more] -= 1.0 - probabilities[less];
+
+            if probabilities[more] < 1.0 {
+                large.pop();
+                small.push(more);
+            }
+        }
+
+        // Whatever remains is only off from 1.0 by float rounding
+        for i in small.into_iter().chain(large) {
+            probabilities[i] = 1.0;
+        }
+
+        Some(Self {
+            probabilities,
+            aliases,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.probabilities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.probabilities.is_empty()
+    }
+
+    #[inline]
+    pub fn sample<R: Generator>(&self, rng: &PetriRand<R>) -> usize {
+        let column = rng.index(..self.probabilities.len());
+
+        if rng.get_f64() < self.probabilities[column] {
+            column
+        } else {
+            self.aliases[column]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_weights() {
+        assert!(WeightedIndex::new(vec![]).is_none());
+        assert!(WeightedIndex::new(vec![0.0, 0.0]).is_none());
+        assert!(WeightedIndex::new(vec![1.0, -1.0]).is_none());
+        assert!(WeightedIndex::new(vec![1.0, f32::NAN]).is_none());
+    }
+
+    #[test]
+    fn sample() {
+        let rng = PetriRand::with_seed(Default::default());
+        let weights = WeightedIndex::new(vec![0.0, 2.0, 1.0, 4.0, 3.0]).unwrap();
+
+        let mut histogram = [0usize; 5];
+        for _ in 0..10_000 {
+            histogram[weights.sample(&rng)] += 1;
+        }
+
+        assert_eq!(histogram[0], 0);
+
+        for (count, weight) in histogram.iter().zip([0.0, 2.0, 1.0, 4.0, 3.0]) {
+            let expected = 10_000.0 * weight / 10.0;
+            assert!((*count as f64 - expected).abs() < 150.0);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Statistical quality checks for the generators and the helpers built on top of
+//! them. Every check runs over a fixed set of seeds for each generator, so results
+//! are reproducible and need nothing but the crate itself. Critical values are
+//! for a 0.1% significance level unless noted otherwise.
+
+use petri_rand::{Generator, PetriRand, WyRand, Xoshiro256PlusPlus};
+
+const SEEDS: [u64; 4] = [0, 1, 0xdead_beef, u64::MAX];
+const SAMPLES: usize = 100_000;
+
+/// Standard normal quantiles for one and two-sided 0.1% significance levels.
+const Z_CRITICAL: f64 = 3.09;
+const Z_CRITICAL_TWO_SIDED: f64 = 3.29;
+
+/// Runs `check` for every seed with every generator family.
+fn for_each_generator(check: impl Fn(&str, &dyn Source)) {
+    for seed in SEEDS {
+        check(
+            &format!("WyRand seed {}", seed),
+            &PetriRand::from_generator(WyRand::with_seed(seed)),
+        );
+        check(
+            &format!("Xoshiro256++ seed {}", seed),
+            &PetriRand::from_generator(Xoshiro256PlusPlus::with_seed(seed)),
+        );
+    }
+}
+
+/// Object safe view of the methods under test, so checks don't need to be
+/// generic over each generator.
+trait Source {
+    fn get_u32(&self) -> u32;
+    fn get_u64(&self) -> u64;
+    fn get_f32(&self) -> f32;
+    fn index(&self, bounds: std::ops::Range<usize>) -> usize;
+    fn chance(&self, rate: f64) -> bool;
+}
+
+impl<R: Generator> Source for PetriRand<R> {
+    fn get_u32(&self) -> u32 {
+        PetriRand::get_u32(self)
+    }
+
+    fn get_u64(&self) -> u64 {
+        PetriRand::get_u64(self)
+    }
+
+    fn get_f32(&self) -> f32 {
+        PetriRand::get_f32(self)
+    }
+
+    fn index(&self, bounds: std::ops::Range<usize>) -> usize {
+        PetriRand::index(self, bounds)
+    }
+
+    fn chance(&self, rate: f64) -> bool {
+        PetriRand::chance(self, rate)
+    }
+}
+
+/// Upper critical value of the chi-squared distribution, using the
+/// Wilson-Hilferty approximation.
+fn chi_squared_critical(degrees_of_freedom: usize) -> f64 {
+    let k = degrees_of_freedom as f64;
+    let term = 2.0 / (9.0 * k);
+
+    k * (1.0 - term + Z_CRITICAL * term.sqrt()).powi(3)
+}
+
+/// Chi-squared goodness of fit of `observed` counts against a uniform distribution.
+fn assert_chi_squared_uniform(name: &str, observed: &[usize]) {
+    let total: usize = observed.iter().sum();
+    let expected = total as f64 / observed.len() as f64;
+
+    let statistic: f64 = observed
+        .iter()
+        .map(|&count| (count as f64 - expected).powi(2) / expected)
+        .sum();
+
+    let critical = chi_squared_critical(observed.len() - 1);
+
+    assert!(
+        statistic < critical,
+        "{}: chi-squared {} exceeds critical value {}",
+        name,
+        statistic,
+        critical
+    );
+}
+
+#[test]
+fn u32_chi_squared() {
+    for_each_generator(|name, rng| {
+        let mut high = [0usize; 256];
+        let mut low = [0usize; 256];
+
+        for _ in 0..SAMPLES {
+            let value = rng.get_u32();
+            high[(value >> 24) as usize] += 1;
+            low[(value & 0xff) as usize] += 1;
+        }
+
+        assert_chi_squared_uniform(&format!("{} high byte", name), &high);
+        assert_chi_squared_uniform(&format!("{} low byte", name), &low);
+    });
+}
+
+#[test]
+fn bit_frequency() {
+    // Bonferroni corrected for 96 bit positions per generator
+    const BIT_CRITICAL: f64 = 4.0;
+
+    for_each_generator(|name, rng| {
+        let mut ones_u32 = [0usize; 32];
+        let mut ones_u64 = [0usize; 64];
+
+        for _ in 0..SAMPLES {
+            let value = rng.get_u32();
+            ones_u32
+                .iter_mut()
+                .enumerate()
+                .for_each(|(bit, count)| *count += ((value >> bit) & 1) as usize);
+
+            let value = rng.get_u64();
+            ones_u64
+                .iter_mut()
+                .enumerate()
+                .for_each(|(bit, count)| *count += ((value >> bit) & 1) as usize);
+        }
+
+        let std_dev = (SAMPLES as f64 / 4.0).sqrt();
+
+        for (bit, &ones) in ones_u32.iter().chain(ones_u64.iter()).enumerate() {
+            let z = (ones as f64 - SAMPLES as f64 / 2.0) / std_dev;
+
+            assert!(
+                z.abs() < BIT_CRITICAL,
+                "{}: bit {} set {} times out of {}",
+                name,
+                bit,
+                ones,
+                SAMPLES
+            );
+        }
+    });
+}
+
+#[test]
+fn f32_kolmogorov_smirnov() {
+    for_each_generator(|name, rng| {
+        let mut samples: Vec<f32> = (0..SAMPLES).map(|_| rng.get_f32()).collect();
+
+        assert!(
+            samples.iter().all(|value| (0.0..1.0).contains(value)),
+            "{}: get_f32 yielded a value outside of [0.0, 1.0)",
+            name
+        );
+
+        samples.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let n = SAMPLES as f64;
+        let statistic = samples
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                let value = value as f64;
+                (value - i as f64 / n).max((i + 1) as f64 / n - value)
+            })
+            .fold(0.0, f64::max);
+
+        let critical = 1.95 / n.sqrt();
+
+        assert!(
+            statistic < critical,
+            "{}: KS statistic {} exceeds critical value {}",
+            name,
+            statistic,
+            critical
+        );
+    });
+}
+
+#[test]
+fn f32_runs() {
+    // Wald-Wolfowitz runs test above/below the median of 0.5
+    for_each_generator(|name, rng| {
+        let above: Vec<bool> = (0..SAMPLES).map(|_| rng.get_f32() >= 0.5).collect();
+
+        let n1 = above.iter().filter(|&&above| above).count() as f64;
+        let n2 = SAMPLES as f64 - n1;
+        let runs = 1 + above.windows(2).filter(|pair| pair[0] != pair[1]).count();
+
+        let expected = 2.0 * n1 * n2 / (n1 + n2) + 1.0;
+        let variance = (expected - 1.0) * (expected - 2.0) / (n1 + n2 - 1.0);
+        let z = (runs as f64 - expected) / variance.sqrt();
+
+        assert!(
+            z.abs() < Z_CRITICAL_TWO_SIDED,
+            "{}: {} runs, expected {} (z = {})",
+            name,
+            runs,
+            expected,
+            z
+        );
+    });
+}
+
+#[test]
+fn index_chi_squared() {
+    for_each_generator(|name, rng| {
+        // Powers of two and otherwise, as the rejection path only kicks in for the latter
+        for upper in [2, 7, 10, 64, 1000] {
+            let mut histogram = vec![0usize; upper];
+
+            for _ in 0..SAMPLES {
+                histogram[rng.index(0..upper)] += 1;
+            }
+
+            assert_chi_squared_uniform(&format!("{} index(0..{})", name, upper), &histogram);
+        }
+    });
+}
+
+#[test]
+fn chance_frequency() {
+    for_each_generator(|name, rng| {
+        for rate in [0.01, 0.3, 0.5, 0.99] {
+            let hits = (0..SAMPLES).filter(|_| rng.chance(rate)).count();
+
+            let expected = SAMPLES as f64 * rate;
+            let std_dev = (SAMPLES as f64 * rate * (1.0 - rate)).sqrt();
+            let z = (hits as f64 - expected) / std_dev;
+
+            assert!(
+                z.abs() < Z_CRITICAL_TWO_SIDED,
+                "{}: chance({}) hit {} times, expected {}",
+                name,
+                rate,
+                hits,
+                expected
+            );
+        }
+
+        assert_eq!((0..1000).filter(|_| rng.chance(0.0)).count(), 0);
+        assert_eq!((0..1000).filter(|_| rng.chance(1.0)).count(), 1000);
+    });
+}
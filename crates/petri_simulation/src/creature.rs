@@ -1,12 +1,13 @@
-use std::{
-    f32::consts::{FRAC_2_PI, PI},
-    iter::repeat_with,
-};
+use std::f32::consts::{FRAC_2_PI, PI};
 
-use crate::{materials::Materials, simulation::Simulation, Eye};
+use crate::{
+    materials::Materials,
+    simulation::{RngStream, Simulation, SimulationSeed},
+    utils::layout_positions,
+    Eye,
+};
 use bevy::prelude::*;
-use petri_nn::Network;
-use petri_rand::PetriRand;
+use petri_nn::{HiddenState, Network, Scratch};
 
 #[derive(Component, Debug, Default)]
 pub struct Creature;
@@ -28,46 +29,55 @@ pub struct CreatureBundle {
     pub control: Control,
     pub eye: Eye,
     pub brain: Network,
+    pub memory: HiddenState,
+    pub scratch: Scratch,
     pub fitness: Fitness,
     #[bundle]
     pub sprite: SpriteBundle,
 }
 
-pub fn creature_setup(mut commands: Commands, materials: Res<Materials>, sim: Res<Simulation>) {
-    let rng = PetriRand::new();
+pub fn creature_setup(
+    mut commands: Commands,
+    materials: Res<Materials>,
+    sim: Res<Simulation>,
+    seed: Res<SimulationSeed>,
+) {
+    let rng = seed.rng(RngStream::CreatureSetup, 0, 0);
 
-    let creatures: Vec<CreatureBundle> = repeat_with(|| {
-        let translation = Vec3::new(
-            rng.get_f32() * sim.world.x,
-            rng.get_f32() * sim.world.y,
-            1.0,
-        );
-        let rotation = Quat::from_rotation_z(rng.get_f32() * 2.0 * PI);
-        let scale = Vec2::splat(0.07).extend(1.0);
+    let positions = layout_positions(sim.layout, &rng, sim.world, sim.creatures);
 
-        CreatureBundle {
-            creature: Creature,
-            sprite: SpriteBundle {
-                texture: materials.creature.clone(),
-                transform: Transform {
-                    translation,
-                    rotation,
-                    scale,
+    let creatures: Vec<CreatureBundle> = positions
+        .into_iter()
+        .map(|position| {
+            let translation = position.extend(1.0);
+            let rotation = Quat::from_rotation_z(rng.range_f32(0.0..2.0 * PI));
+            let scale = Vec2::splat(0.07).extend(1.0);
+            let brain = Network::random(&rng, sim.brain.clone());
+
+            CreatureBundle {
+                creature: Creature,
+                sprite: SpriteBundle {
+                    texture: materials.creature.clone(),
+                    transform: Transform {
+                        translation,
+                        rotation,
+                        scale,
+                    },
+                    ..Default::default()
+                },
+                control: Default::default(),
+                fitness: Default::default(),
+                eye: Eye {
+                    fov_range: 200.0,
+                    fov_angle: FRAC_2_PI,
+                    cells: sim.brain.input_size(),
                 },
-                ..Default::default()
-            },
-            control: Default::default(),
-            fitness: Default::default(),
-            eye: Eye {
-                fov_range: 200.0,
-                fov_angle: FRAC_2_PI,
-                cells: 11,
-            },
-            brain: Network::random(&rng, vec![11, 22, 11, 6, 3]),
-        }
-    })
-    .take(sim.creatures)
-    .collect();
+                memory: brain.hidden_state(),
+                scratch: brain.scratch(),
+                brain,
+            }
+        })
+        .collect();
 
     commands.spawn_batch(creatures);
 }
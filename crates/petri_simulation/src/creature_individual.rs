@@ -1,4 +1,4 @@
-use petri_ga::{Chromosome, Individual};
+use petri_ga::{Candidate, Chromosome, Individual};
 use petri_nn::Network;
 
 use crate::*;
@@ -10,18 +10,33 @@ pub struct CreatureIndividual {
 }
 
 impl CreatureIndividual {
+    /// The chromosome holds the brain's activation genes, followed by its weights.
     pub fn from_creature(brain: &Network, fitness: &Fitness) -> Self {
         Self {
             fitness: fitness.score,
-            chromosome: brain.weights().collect(),
+            chromosome: brain.activation_genes().chain(brain.weights()).collect(),
         }
     }
+
+    pub fn into_creature(self, brain: &mut Network, fitness: &mut Fitness) {
+        let activations = brain.activations().len();
+
+        fitness.score = self.fitness;
+
+        let mut genes = self.into_iter();
+
+        brain.adjust_activations(genes.by_ref().take(activations));
+        brain.adjust_weights(genes);
+    }
 }
 
-impl Individual for CreatureIndividual {
+impl Candidate for CreatureIndividual {
     fn fitness(&self) -> f32 {
         self.fitness
     }
+}
+
+impl Individual for CreatureIndividual {
     fn chromosome(&self) -> &Chromosome {
         &self.chromosome
     }
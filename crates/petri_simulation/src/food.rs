@@ -1,7 +1,4 @@
-use std::iter::repeat_with;
-
-use crate::*;
-use petri_rand::PetriRand;
+use crate::{utils::layout_positions, *};
 
 #[derive(Component, Debug, Default)]
 pub struct Food;
@@ -13,32 +10,36 @@ pub struct FoodBundle {
     pub sprite: SpriteBundle,
 }
 
-pub(crate) fn food_setup(mut commands: Commands, materials: Res<Materials>, sim: Res<Simulation>) {
-    let rng = PetriRand::new();
+pub(crate) fn food_setup(
+    mut commands: Commands,
+    materials: Res<Materials>,
+    sim: Res<Simulation>,
+    seed: Res<SimulationSeed>,
+) {
+    let rng = seed.rng(RngStream::FoodSetup, 0, 0);
+
+    let positions = layout_positions(sim.layout, &rng, sim.world, sim.food);
 
-    let food: Vec<FoodBundle> = repeat_with(|| {
-        let translation = Vec3::new(
-            rng.get_f32() * sim.world.x,
-            rng.get_f32() * sim.world.y,
-            0.0,
-        );
-        let scale = Vec2::splat(0.05).extend(1.0);
+    let food: Vec<FoodBundle> = positions
+        .into_iter()
+        .map(|position| {
+            let translation = position.extend(0.0);
+            let scale = Vec2::splat(0.05).extend(1.0);
 
-        FoodBundle {
-            sprite: SpriteBundle {
-                texture: materials.food.clone(),
-                transform: Transform {
-                    translation,
-                    scale,
+            FoodBundle {
+                sprite: SpriteBundle {
+                    texture: materials.food.clone(),
+                    transform: Transform {
+                        translation,
+                        scale,
+                        ..Default::default()
+                    },
                     ..Default::default()
                 },
                 ..Default::default()
-            },
-            ..Default::default()
-        }
-    })
-    .take(sim.food)
-    .collect();
+            }
+        })
+        .collect();
 
     commands.spawn_batch(food);
 }
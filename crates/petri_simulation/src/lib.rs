@@ -50,17 +50,19 @@ impl Plugin for SimulationPlugin {
                     .with_run_criteria(FixedTimestep::step(SIM_UPDATE))
                     .with_system(detect_food_collisions.label("detect"))
                     .with_system(creatures_thinking.label("thinking").after("detect"))
-                    .with_system(move_creatures.after("thinking"))
-                    .with_system(update_lifecycle.after("thinking")),
+                    .with_system(move_creatures.label("moving").after("thinking"))
+                    .with_system(update_lifecycle.after("moving")),
             )
             .add_system_set(
                 SystemSet::new()
                     .label("evolving")
                     .after("running")
                     .with_run_criteria(evolve_when_ready)
-                    .with_system(evolve_creatures.chain(log_stats))
-                    .with_system(randomise_food)
-                    .with_system(reset_lifecycle),
+                    .with_system(evolve_creatures.chain(log_stats).label("evolve"))
+                    .with_system(randomise_food.label("randomise"))
+                    // Both the above derive their RNG from the current generation,
+                    // so only advance it once they are done
+                    .with_system(reset_lifecycle.after("evolve").after("randomise")),
             );
     }
 }
@@ -5,10 +5,7 @@ pub struct Materials {
     pub food: Handle<Image>,
 }
 
-pub(crate) fn material_setup(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-) {
+pub(crate) fn material_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     let _scenes: Vec<HandleUntyped> = asset_server.load_folder("sprites/").unwrap();
 
     commands.insert_resource(Materials {
@@ -6,8 +6,8 @@ use bevy::{ecs::schedule::ShouldRun, tasks::ComputeTaskPool};
 use petri_ga::{
     GaussianMutation, GeneticAlgorithm, RouletteWheelSelection, Statistics, UniformCrossover,
 };
-use petri_nn::Network;
-use petri_rand::PetriRand;
+use petri_nn::{HiddenState, LayerKind, Network, Scratch, Topology};
+use petri_rand::{DefaultEntropy, EntropySource, PetriRand, SEED_VAR};
 
 const SPEED_MIN: f32 = 0.05;
 const SPEED_MAX: f32 = 5.5;
@@ -20,12 +20,67 @@ pub struct Simulation {
     pub world: Vec2,
     pub creatures: usize,
     pub food: usize,
+    pub layout: Layout,
+    /// Topology of every creature's brain, with inputs matching its eye's cells
+    /// and three outputs for steering.
+    pub brain: Topology,
+}
+
+/// How creatures and food are spread over the world when they are (re)placed
+/// at the start of a generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// Independent uniform positions, which can clump together or leave gaps.
+    Random,
+    /// Evenly spread positions from a randomly rotated Halton sequence.
+    Halton,
+    /// Evenly spread positions from a randomly offset R2 sequence.
+    R2,
+    /// Random positions kept a minimum distance apart.
+    PoissonDisk,
 }
 
 #[derive(Debug, Default)]
 pub struct Lifecycle {
     pub limit: usize,
     pub step: usize,
+    pub generation: usize,
+}
+
+impl Lifecycle {
+    /// Whether the current generation has run all its steps and is waiting to evolve.
+    pub fn is_complete(&self) -> bool {
+        self.step >= self.limit
+    }
+}
+
+/// Master seed that every random stream of the simulation is derived from.
+/// Insert it as a resource before adding the `SimulationPlugin` to replay a run,
+/// otherwise it is read from `PETRI_SEED`, or freshly generated at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationSeed(pub u64);
+
+/// The independent random streams derived from the `SimulationSeed`, one per
+/// kind of system that needs randomness.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum RngStream {
+    CreatureSetup,
+    FoodSetup,
+    FoodCollision,
+    FoodRandomise,
+    Evolution,
+}
+
+impl SimulationSeed {
+    /// Derives the generator for a stream at a given point of the run. The result
+    /// only depends on the seed and arguments, so it is the same on every replay
+    /// regardless of system scheduling.
+    pub(crate) fn rng(&self, stream: RngStream, generation: usize, step: usize) -> PetriRand {
+        PetriRand::with_seed(self.0)
+            .fork(stream as u64)
+            .fork(generation as u64)
+            .fork(step as u64)
+    }
 }
 
 #[derive(Debug)]
@@ -33,11 +88,31 @@ pub struct Evolver {
     ga: GeneticAlgorithm<'static, RouletteWheelSelection, UniformCrossover, GaussianMutation>,
 }
 
-pub(crate) fn simulation_setup(mut commands: Commands) {
+pub(crate) fn simulation_setup(mut commands: Commands, seed: Option<Res<SimulationSeed>>) {
+    let seed = match seed {
+        Some(seed) => *seed,
+        None => {
+            let seed = SimulationSeed(DefaultEntropy::default().seed());
+            commands.insert_resource(seed);
+            seed
+        }
+    };
+
+    info!(
+        "Simulation seed: {} (set {}={} to replay this run)",
+        seed.0, SEED_VAR, seed.0
+    );
+
+    // The second hidden layer is recurrent, giving creatures some memory
+    let mut brain = Topology::dense(&[11, 22, 11, 6, 3]);
+    brain.layers[1].kind = LayerKind::Elman;
+
     commands.insert_resource(Simulation {
         world: Vec2::splat(800.0),
         creatures: 40,
         food: 60,
+        layout: Layout::Random,
+        brain,
     });
 
     commands.insert_resource(Evolver {
@@ -51,6 +126,7 @@ pub(crate) fn simulation_setup(mut commands: Commands) {
     commands.insert_resource(Lifecycle {
         limit: 2000,
         step: 0,
+        generation: 0,
     });
 }
 
@@ -58,8 +134,18 @@ pub(crate) fn detect_food_collisions(
     mut q_food: Query<&mut Transform, (With<Food>, Without<Creature>)>,
     mut q_creatures: Query<(&Transform, &mut Fitness), (With<Creature>, Without<Food>)>,
     sim: Res<Simulation>,
+    seed: Res<SimulationSeed>,
+    lifecycle: Res<Lifecycle>,
 ) {
-    let rng = PetriRand::new();
+    if lifecycle.is_complete() {
+        return;
+    }
+
+    let rng = seed.rng(
+        RngStream::FoodCollision,
+        lifecycle.generation,
+        lifecycle.step,
+    );
 
     for (creature, mut fitness) in q_creatures.iter_mut() {
         for mut food in q_food.iter_mut() {
@@ -68,8 +154,11 @@ pub(crate) fn detect_food_collisions(
             if distance < 7.0 {
                 fitness.score += 1.0;
 
-                food.translation =
-                    Vec2::new(rng.get_f32() * sim.world.x, rng.get_f32() * sim.world.y).extend(0.0);
+                food.translation = Vec2::new(
+                    rng.range_f32(0.0..sim.world.x),
+                    rng.range_f32(0.0..sim.world.y),
+                )
+                .extend(0.0);
             }
         }
     }
@@ -77,33 +166,54 @@ pub(crate) fn detect_food_collisions(
 
 pub(crate) fn creatures_thinking(
     mut creatures: Query<
-        (&Transform, &mut Control, &Eye, &Network),
+        (
+            &Transform,
+            &mut Control,
+            &Eye,
+            &Network,
+            &mut HiddenState,
+            &mut Scratch,
+        ),
         (With<Creature>, Without<Food>),
     >,
     food: Query<&Transform, (With<Food>, Without<Creature>)>,
     pool: Res<ComputeTaskPool>,
+    lifecycle: Res<Lifecycle>,
 ) {
-    creatures.par_for_each_mut(&pool, 10, |(creature, mut control, eye, brain)| {
-        let vision = eye.perceive(creature, food.iter());
+    if lifecycle.is_complete() {
+        return;
+    }
 
-        let vision = brain.propagate(vision);
+    creatures.par_for_each_mut(
+        &pool,
+        10,
+        |(creature, mut control, eye, brain, mut memory, mut scratch)| {
+            let vision = eye.perceive(creature, food.iter());
 
-        let r0 = vision[0].clamp(0.0, 1.0);
-        let r1 = vision[1].clamp(0.0, 1.0) - 0.5;
-        let r2 = vision[2].clamp(0.0, 1.0);
+            let vision = brain.propagate_into_with_state(&vision, &mut scratch, &mut memory);
 
-        let speed = r1.clamp(-SPEED_ACCEL, SPEED_ACCEL);
+            let r0 = vision[0].clamp(0.0, 1.0);
+            let r1 = vision[1].clamp(0.0, 1.0) - 0.5;
+            let r2 = vision[2].clamp(0.0, 1.0);
 
-        control.speed = (control.speed + speed - FRICTION).clamp(SPEED_MIN, SPEED_MAX);
+            let speed = r1.clamp(-SPEED_ACCEL, SPEED_ACCEL);
 
-        control.rotation += (r0 - r2).clamp(-ROTATION_ACCEL, ROTATION_ACCEL);
-    });
+            control.speed = (control.speed + speed - FRICTION).clamp(SPEED_MIN, SPEED_MAX);
+
+            control.rotation += (r0 - r2).clamp(-ROTATION_ACCEL, ROTATION_ACCEL);
+        },
+    );
 }
 
 pub(crate) fn move_creatures(
     mut creatures: Query<(&mut Transform, &Control), With<Creature>>,
     sim: Res<Simulation>,
+    lifecycle: Res<Lifecycle>,
 ) {
+    if lifecycle.is_complete() {
+        return;
+    }
+
     for (mut transform, control) in creatures.iter_mut() {
         let rot = Quat::from_rotation_z(control.rotation);
         transform.rotation = rot;
@@ -114,11 +224,15 @@ pub(crate) fn move_creatures(
 }
 
 pub(crate) fn update_lifecycle(mut lifecycle: ResMut<Lifecycle>) {
-    lifecycle.step += 1;
+    // The fixed timestep can run several steps per frame, so stop counting once
+    // the generation is complete to keep every generation exactly `limit` long.
+    if !lifecycle.is_complete() {
+        lifecycle.step += 1;
+    }
 }
 
 pub(crate) fn evolve_when_ready(lifecycle: Res<Lifecycle>) -> ShouldRun {
-    if lifecycle.step >= lifecycle.limit {
+    if lifecycle.is_complete() {
         ShouldRun::Yes
     } else {
         ShouldRun::No
@@ -129,49 +243,61 @@ pub(crate) fn evolve_creatures(
     mut creatures: Query<(&mut Network, &mut Fitness, &mut Transform), With<Creature>>,
     sim: Res<Simulation>,
     evolver: Res<Evolver>,
+    seed: Res<SimulationSeed>,
+    lifecycle: Res<Lifecycle>,
 ) -> Statistics {
     let population: Vec<CreatureIndividual> = creatures
         .iter()
         .map(|(brain, fitness, _)| CreatureIndividual::from_creature(brain, fitness))
         .collect();
 
-    let rng = PetriRand::new();
+    let rng = seed.rng(RngStream::Evolution, lifecycle.generation, 0);
 
     let (new_population, stats) = evolver.ga.evolve(&rng, &population).unwrap();
 
-    creatures.iter_mut().zip(new_population).for_each(
-        |((mut brain, mut fitness, mut transform), individual)| {
-            fitness.score = individual.fitness;
-            brain.adjust_weights(individual);
-
-            transform.translation = Vec3::new(
-                rng.get_f32() * sim.world.x,
-                rng.get_f32() * sim.world.y,
-                1.0,
-            );
-            transform.rotation = Quat::from_rotation_z(rng.get_f32() * 2.0 * PI);
-        },
-    );
+    let positions = layout_positions(sim.layout, &rng, sim.world, new_population.len());
+
+    creatures
+        .iter_mut()
+        .zip(new_population)
+        .zip(positions)
+        .for_each(
+            |(((mut brain, mut fitness, mut transform), individual), position)| {
+                individual.into_creature(&mut brain, &mut fitness);
+
+                transform.translation = position.extend(1.0);
+                transform.rotation = Quat::from_rotation_z(rng.range_f32(0.0..2.0 * PI));
+            },
+        );
 
     stats
 }
 
-pub(crate) fn reset_lifecycle(mut lifecycle: ResMut<Lifecycle>) {
+pub(crate) fn reset_lifecycle(
+    mut lifecycle: ResMut<Lifecycle>,
+    mut memories: Query<&mut HiddenState, With<Creature>>,
+) {
     lifecycle.step = 0;
+    lifecycle.generation += 1;
+
+    // The next generation has new brains, so shouldn't remember the old ones' thoughts
+    for mut memory in memories.iter_mut() {
+        memory.reset();
+    }
 }
 
 pub(crate) fn randomise_food(
     mut foods: Query<&mut Transform, (With<Food>, Without<Creature>)>,
     sim: Res<Simulation>,
+    seed: Res<SimulationSeed>,
+    lifecycle: Res<Lifecycle>,
 ) {
-    let rng = PetriRand::new();
+    let rng = seed.rng(RngStream::FoodRandomise, lifecycle.generation, 0);
 
-    for mut food in foods.iter_mut() {
-        food.translation = Vec3::new(
-            rng.get_f32() * sim.world.x,
-            rng.get_f32() * sim.world.y,
-            0.0,
-        );
+    let positions = layout_positions(sim.layout, &rng, sim.world, sim.food);
+
+    for (mut food, position) in foods.iter_mut().zip(positions) {
+        food.translation = position.extend(0.0);
     }
 }
 
@@ -1,3 +1,10 @@
+use std::iter::repeat_with;
+
+use bevy::math::Vec2;
+use petri_rand::{Halton, PetriRand, PoissonDisk, R2};
+
+use crate::Layout;
+
 pub(crate) fn wrap(mut value: f32, min: f32, max: f32) -> f32 {
     debug_assert!(min < max, "Invalid bounds");
     let width = max - min;
@@ -18,3 +25,46 @@ pub(crate) fn wrap(mut value: f32, min: f32, max: f32) -> f32 {
 
     value
 }
+
+/// Generates `count` positions within `[0, world)` spread according to `layout`.
+pub(crate) fn layout_positions(
+    layout: Layout,
+    rng: &PetriRand,
+    world: Vec2,
+    count: usize,
+) -> Vec<Vec2> {
+    let random = || Vec2::new(rng.range_f32(0.0..world.x), rng.range_f32(0.0..world.y));
+    let scale = |[x, y]: [f64; 2]| Vec2::new(x as f32, y as f32) * world;
+
+    let mut positions: Vec<Vec2> = match layout {
+        Layout::Random => repeat_with(random).take(count).collect(),
+        Layout::Halton => Halton::new([rng.get_f64(), rng.get_f64()])
+            .take(count)
+            .map(scale)
+            .collect(),
+        Layout::R2 => R2::new([rng.get_f64(), rng.get_f64()])
+            .take(count)
+            .map(scale)
+            .collect(),
+        Layout::PoissonDisk => {
+            // A spacing at which a filled world reliably holds a few more points
+            // than needed, then pick from all over it rather than the ones that
+            // happened to be placed first.
+            let radius = (world.x * world.y / count.max(1) as f32).sqrt() * 0.7;
+            let mut points = PoissonDisk::new(world.x, world.y, radius).sample(rng);
+
+            rng.shuffle(&mut points);
+
+            points
+                .into_iter()
+                .take(count)
+                .map(|[x, y]| Vec2::new(x, y))
+                .collect()
+        }
+    };
+
+    // Poisson-disk sampling is not guaranteed to fit everything, so top up
+    positions.extend(repeat_with(random).take(count - positions.len()));
+
+    positions
+}